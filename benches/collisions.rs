@@ -0,0 +1,103 @@
+//! Times collision detection for growing numbers of particles, with the
+//! spatial hash and, while it stays bearable, by comparing every pair.
+//!
+//! Run with `cargo bench --bench collisions`. This uses a plain `main`
+//! rather than a benchmarking framework, so numbers are indicative only.
+
+use confetty_rs::collision::Collisions;
+use confetty_rs::consts::FIXED_TIMESTEP;
+use confetty_rs::projectile::Projectile;
+use confetty_rs::simulation::Simulation;
+use confetty_rs::simulation_confetti::SimulationStateConfetti;
+use nalgebra::{Point2, Vector2};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use ratatui::layout::Rect;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// A large terminal, about 48 000 cells.
+const AREA: Rect = Rect {
+    x: 0,
+    y: 0,
+    width: 400,
+    height: 120,
+};
+
+/// Brute force stops being worth waiting for past this many particles.
+const MAX_BRUTE_FORCE: usize = 5_000;
+
+/// Returns the mean time `f` takes over `runs` runs.
+fn time(runs: u32, mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..runs {
+        f();
+    }
+    start.elapsed() / runs
+}
+
+/// Scatters `count` particles uniformly over the area.
+fn particles(count: usize) -> Vec<Projectile> {
+    let mut rng = StdRng::seed_from_u64(0x5EED);
+    (0..count)
+        .map(|_| {
+            let pos = Point2::new(
+                rng.gen_range(0.0..f32::from(AREA.width)),
+                rng.gen_range(0.0..f32::from(AREA.height)),
+            );
+            let vel = Vector2::new(rng.gen_range(-20.0..20.0), rng.gen_range(-20.0..20.0));
+            Projectile::new(pos, vel, Vector2::zeros())
+        })
+        .collect()
+}
+
+/// Counts overlapping pairs by comparing every pair, the baseline the
+/// spatial hash saves us from.
+fn brute_force(bodies: &[Projectile], diameter: f32) -> usize {
+    let mut contacts = 0;
+    for (i, a) in bodies.iter().enumerate() {
+        for b in &bodies[i + 1..] {
+            if (b.position() - a.position()).norm() < diameter {
+                contacts += 1;
+            }
+        }
+    }
+    contacts
+}
+
+fn main() {
+    let collisions = Collisions::default();
+    println!(
+        "{:>9}  {:>12}  {:>12}",
+        "particles", "spatial hash", "brute force"
+    );
+    for count in [1_000, 5_000, 10_000, 25_000, 50_000] {
+        let bodies = particles(count);
+        let hashed = time(20, || {
+            let mut bodies = bodies.clone();
+            black_box(collisions.resolve(AREA, &mut bodies, |body| body));
+        });
+        let brute = if count <= MAX_BRUTE_FORCE {
+            let brute = time(3, || {
+                black_box(brute_force(&bodies, 2.0 * collisions.radius));
+            });
+            format!("{brute:?}")
+        } else {
+            "-".to_string()
+        };
+        println!("{count:>9}  {:>12}  {brute:>12}", format!("{hashed:?}"));
+    }
+
+    // A whole confetti tick with collisions on, at the same counts.
+    println!();
+    println!("{:>9}  {:>12}", "particles", "confetti tick");
+    for count in [1_000, 10_000, 50_000] {
+        let mut confetti = SimulationStateConfetti::with_seed(0x5EED);
+        confetti.set_collisions(Some(collisions));
+        while confetti.particle_count() < count {
+            confetti.spawn_particles(AREA);
+        }
+        let tick = time(20, || confetti.tick(AREA, FIXED_TIMESTEP));
+        println!("{count:>9}  {:>12}", format!("{tick:?}"));
+    }
+}
@@ -0,0 +1,85 @@
+//! Embeds the confetti simulation inside a bordered block of a larger
+//! dashboard, as an overlay that slips behind the block's text. Press any
+//! key to celebrate, `q` to quit.
+
+use confetty_rs::app::AppResult;
+use confetty_rs::consts::TICK_RATE_IN_MILI;
+use confetty_rs::event::{Event, EventHandler};
+use confetty_rs::render::RenderOptions;
+use confetty_rs::simulation::Simulation;
+use confetty_rs::simulation_confetti::SimulationStateConfetti;
+use crossterm::event::KeyCode;
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, List, Paragraph};
+use std::io;
+
+struct Dashboard {
+    confetti: SimulationStateConfetti,
+    /// Where the confetti was last drawn, so ticks and spawns use the same
+    /// bounds as rendering.
+    confetti_area: Rect,
+    deploys: Vec<String>,
+}
+
+impl Dashboard {
+    fn render(&mut self, frame: &mut Frame) {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+            .split(frame.size());
+        let (jobs, celebration) = (chunks[0], chunks[1]);
+
+        frame.render_widget(
+            List::new(self.deploys.clone())
+                .block(Block::default().title("Deploys").borders(Borders::ALL)),
+            jobs,
+        );
+
+        let block = Block::default()
+            .title("Celebration (any key, q to quit)")
+            .borders(Borders::ALL);
+        self.confetti_area = block.inner(celebration);
+        frame.render_widget(block, celebration);
+        frame.render_widget(
+            Paragraph::new(format!("{} deploys shipped", self.deploys.len()))
+                .alignment(Alignment::Center),
+            self.confetti_area,
+        );
+        frame.render_widget(&self.confetti, self.confetti_area);
+    }
+}
+
+fn main() -> AppResult<()> {
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stderr()))?;
+    terminal::enable_raw_mode()?;
+    crossterm::execute!(io::stderr(), EnterAlternateScreen)?;
+
+    let events = EventHandler::new(TICK_RATE_IN_MILI);
+    let mut confetti = SimulationStateConfetti::new();
+    confetti.set_render_options(RenderOptions::overlay());
+    let mut dashboard = Dashboard {
+        confetti,
+        confetti_area: Rect::default(),
+        deploys: vec![],
+    };
+
+    loop {
+        terminal.draw(|frame| dashboard.render(frame))?;
+        match events.next()? {
+            Event::Tick(elapsed) => dashboard.confetti.tick(dashboard.confetti_area, elapsed),
+            Event::Key(key) if key.code == KeyCode::Char('q') => break,
+            Event::Key(_) => {
+                dashboard
+                    .deploys
+                    .push(format!("v1.{} deployed", dashboard.deploys.len()));
+                dashboard.confetti.spawn_particles(dashboard.confetti_area);
+            }
+            Event::Mouse(_) | Event::Resize(_, _) => {}
+        }
+    }
+
+    terminal::disable_raw_mode()?;
+    crossterm::execute!(io::stderr(), LeaveAlternateScreen)?;
+    Ok(())
+}
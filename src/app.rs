@@ -1,13 +1,151 @@
-use crate::simulation_confetti::SimulationStateConfetti;
-use crate::simulation_fireworks::SimulationStateFireworks;
-use crate::simulation_shooting_star::SimulationStateShootingStar;
-use crate::system::AppSimulation;
+use crate::collision::Collisions;
+use crate::consts::{
+    FIXED_TIMESTEP, MAX_FRAME_TIME, MAX_INTENSITY, MAX_SPEED, MIN_INTENSITY, MIN_SPEED,
+};
+use crate::effect::{EffectDefinition, EmitterDefinition, SimulationStateEffect};
+use crate::emitter::Emitter;
+use crate::forces::Force;
+use crate::glyphs::GlyphSet;
+use crate::keymap::KeyMap;
+use crate::palette::{Palette, PaletteConfig};
+use crate::projectile::Integrator;
+use crate::render::RenderOptions;
+use crate::schedule::Schedule;
+use crate::simulation::Simulation;
+use nalgebra::{Point2, Vector2};
 use ratatui::layout::Rect;
+use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::error;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::time::Duration;
 
 /// Application result type.
 pub type AppResult<T> = std::result::Result<T, Box<dyn error::Error>>;
 
+/// One of the built-in simulations.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Mode {
+    #[default]
+    Confetti,
+    Fireworks,
+    Stars,
+}
+
+impl Mode {
+    /// Returns the effect this kind of simulation runs, bundled under
+    /// `effects/` and parsed the first time it is needed.
+    pub fn definition(self) -> &'static EffectDefinition {
+        static DEFINITIONS: [OnceLock<EffectDefinition>; 3] =
+            [OnceLock::new(), OnceLock::new(), OnceLock::new()];
+        DEFINITIONS[self as usize].get_or_init(|| {
+            EffectDefinition::builtin(&self.to_string()).expect("every mode has a built-in effect")
+        })
+    }
+
+    /// Constructs a fresh simulation of this kind.
+    pub fn simulation(self) -> Box<dyn Simulation> {
+        Box::new(self.with_seed(rand::random()))
+    }
+
+    /// Constructs a simulation of this kind whose particles are drawn from
+    /// `seed`, so the same input always produces the same frames.
+    pub fn with_seed(self, seed: u64) -> SimulationStateEffect {
+        SimulationStateEffect::with_seed(self.definition().clone(), seed)
+            .expect("built-in effects are valid")
+    }
+
+    /// Returns the emitter this kind of simulation spawns bursts from, if
+    /// its effect has any.
+    pub fn emitter(self) -> Option<Emitter> {
+        self.definition()
+            .emitters
+            .first()
+            .map(EmitterDefinition::emitter)
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Mode::Confetti => "confetti",
+            Mode::Fireworks => "fireworks",
+            Mode::Stars => "stars",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Mode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "confetti" => Ok(Mode::Confetti),
+            "fireworks" => Ok(Mode::Fireworks),
+            "stars" => Ok(Mode::Stars),
+            _ => Err(format!(
+                "unknown mode `{s}` (expected confetti, fireworks or stars)"
+            )),
+        }
+    }
+}
+
+/// Options applied to every simulation the [`App`] runs, including the ones
+/// it switches to later.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Settings {
+    pub seed: Option<u64>,
+    pub integrator: Integrator,
+    pub render_options: RenderOptions,
+    /// Decay of the trail left behind particles, if any.
+    pub trail: Option<f32>,
+    pub wind: Option<Vector2<f32>>,
+    /// How particles bounce off one another, if they do.
+    pub collisions: Option<Collisions>,
+    /// Palette of every simulation, overriding [`palettes`](Self::palettes).
+    pub palette: Option<Palette>,
+    /// Palettes of individual built-in simulations.
+    pub palettes: PaletteConfig,
+    /// Glyph set of every simulation without one in
+    /// [`glyph_sets`](Self::glyph_sets).
+    pub glyphs: Option<GlyphSet>,
+    /// Glyph sets of individual built-in simulations.
+    pub glyph_sets: BTreeMap<Mode, GlyphSet>,
+}
+
+impl Settings {
+    /// Configures `state`, the built-in simulation `mode` if it is one, with
+    /// these settings.
+    pub fn apply(&self, mode: Option<Mode>, state: &mut dyn Simulation) {
+        if let Some(seed) = self.seed {
+            state.reseed(seed);
+        }
+        state.set_integrator(self.integrator);
+        state.set_trail(self.trail);
+        state.set_render_options(self.render_options);
+        state.set_collisions(self.collisions);
+        if let (Some(wind), Some(forces)) = (self.wind, state.force_field_mut()) {
+            forces.push(Force::Wind(wind));
+        }
+        let palette = match (&self.palette, mode) {
+            (Some(palette), _) => Some(palette),
+            (None, Some(mode)) => self.palettes.simulations.get(&mode),
+            (None, None) => None,
+        };
+        if let Some(palette) = palette {
+            state.set_palette(palette.clone());
+        }
+        let glyphs = mode.and_then(|mode| self.glyph_sets.get(&mode));
+        if let Some(glyphs) = glyphs.or(self.glyphs.as_ref()) {
+            state.set_glyphs(glyphs);
+        }
+    }
+}
+
 /// Application.
 #[derive(Debug)]
 pub struct App {
@@ -16,19 +154,62 @@ pub struct App {
 
     pub area: Rect,
 
-    pub state: AppSimulation,
+    pub state: Box<dyn Simulation>,
+
+    /// The built-in simulation [`state`](Self::state) is, if it is one.
+    pub mode: Option<Mode>,
+
+    /// Simulations switched away from whose particles are left to finish.
+    pub retired: Vec<Box<dyn Simulation>>,
+
+    /// Applied to [`state`](Self::state) and every simulation switched to.
+    pub settings: Settings,
+
+    pub keymap: KeyMap,
 
     pub num_particles: usize,
+
+    /// Length of one physics step.
+    pub timestep: Duration,
+
+    /// Elapsed time that has not been simulated yet.
+    accumulator: Duration,
+
+    /// Spawns bursts on its own, without user input.
+    pub schedule: Option<Schedule>,
+
+    /// Where the mouse is being dragged, relative to the area, if it is.
+    pub pointer: Option<Point2<f32>>,
+
+    /// Multiplier on the number of particles spawned, adjusted by scrolling.
+    pub intensity: f32,
+
+    /// Keep live particles at the same relative position when the area is
+    /// resized.
+    pub remap_on_resize: bool,
+
+    /// Let the particles of the previous simulation finish when switching
+    /// modes, instead of clearing them.
+    pub carry_over: bool,
+
+    /// Is the simulation frozen? Ticks are ignored, but [`step`](Self::step)
+    /// still advances it.
+    pub paused: bool,
+
+    /// How fast simulated time passes relative to real time.
+    pub speed: f32,
+
+    /// Is the key binding overlay shown?
+    pub show_help: bool,
+
+    /// Spawn a burst as soon as the area is known, so the simulation shows
+    /// something before any input.
+    pub spawn_on_start: bool,
 }
 
 impl Default for App {
     fn default() -> Self {
-        Self {
-            running: true,
-            area: Rect::new(0, 0, 0, 0),
-            state: AppSimulation::Confetti(SimulationStateConfetti::new()), // Default to Fireworks
-            num_particles: 0,
-        }
+        Self::with_mode(Mode::default())
     }
 }
 
@@ -39,29 +220,122 @@ impl App {
     }
 
     pub fn fireworks() -> Self {
+        Self::with_mode(Mode::Fireworks)
+    }
+
+    pub fn shooting_star() -> Self {
+        Self::with_mode(Mode::Stars)
+    }
+
+    /// Constructs an [`App`] running the built-in simulation `mode`.
+    pub fn with_mode(mode: Mode) -> Self {
         Self {
-            running: true,
-            area: Rect::new(0, 0, 0, 0),
-            state: AppSimulation::Fireworks(SimulationStateFireworks::new()), // Default to Fireworks
-            num_particles: 0,
+            mode: Some(mode),
+            ..Self::with_simulation(mode.simulation())
         }
     }
 
-    pub fn shooting_star() -> Self {
+    /// Constructs an [`App`] that drives any [`Simulation`].
+    pub fn with_simulation(state: Box<dyn Simulation>) -> Self {
         Self {
             running: true,
             area: Rect::new(0, 0, 0, 0),
-            state: AppSimulation::ShootingStar(SimulationStateShootingStar::new()), // Default to Fireworks
+            state,
+            mode: None,
+            retired: Vec::new(),
+            settings: Settings::default(),
+            keymap: KeyMap::default(),
             num_particles: 0,
+            timestep: FIXED_TIMESTEP,
+            accumulator: Duration::ZERO,
+            schedule: None,
+            pointer: None,
+            intensity: 1.0,
+            remap_on_resize: false,
+            carry_over: false,
+            paused: false,
+            speed: 1.0,
+            show_help: false,
+            spawn_on_start: false,
         }
     }
+
+    /// Returns the [`App`] spawning bursts on `schedule` as well as on
+    /// demand.
+    pub fn with_schedule(mut self, schedule: Schedule) -> Self {
+        self.schedule = Some(schedule);
+        self
+    }
+
     /// Handles the tick event of the terminal.
-    pub fn tick(&mut self) {
-        match &mut self.state {
-            AppSimulation::Confetti(state) => state.tick(),
-            AppSimulation::Fireworks(state) => state.tick(),
-            AppSimulation::ShootingStar(state) => state.tick(),
+    ///
+    /// `elapsed` is the real time since the previous tick. It is consumed in
+    /// fixed [`timestep`](Self::timestep) increments so the outcome does not
+    /// depend on tick jitter; any remainder carries over to the next tick.
+    ///
+    /// Nothing happens while [`paused`](Self::paused), and `elapsed` is
+    /// scaled by [`speed`](Self::speed) otherwise.
+    pub fn tick(&mut self, elapsed: Duration) {
+        if self.paused {
+            return;
+        }
+        let elapsed = elapsed.min(MAX_FRAME_TIME).as_nanos() as f64 * f64::from(self.speed);
+        self.accumulator += Duration::from_nanos(elapsed.round() as u64);
+        while !self.timestep.is_zero() && self.accumulator >= self.timestep {
+            self.accumulator -= self.timestep;
+            self.step();
+        }
+    }
+
+    /// Advances the simulation by exactly one [`timestep`](Self::timestep),
+    /// whether or not it is paused.
+    pub fn step(&mut self) {
+        self.state.tick(self.area, self.timestep);
+        for retired in &mut self.retired {
+            retired.tick(self.area, self.timestep);
+        }
+        self.retired.retain(|retired| retired.particle_count() > 0);
+
+        let bursts = match &mut self.schedule {
+            Some(schedule) => schedule.advance(self.timestep),
+            None => 0,
+        };
+        for _ in 0..bursts {
+            self.spawn_particles();
         }
+        if let Some(pointer) = self.pointer {
+            self.num_particles += self.state.stream_at(self.area, pointer, self.timestep);
+        }
+    }
+
+    /// Replaces the running simulation with a fresh one of `mode`, configured
+    /// with the [`settings`](Self::settings). Its particles are left to
+    /// finish if [`carry_over`](Self::carry_over) is set, and cleared
+    /// otherwise.
+    pub fn switch_mode(&mut self, mode: Mode) {
+        let mut state = mode.simulation();
+        self.settings.apply(Some(mode), state.as_mut());
+        state.set_intensity(self.intensity);
+
+        let previous = std::mem::replace(&mut self.state, state);
+        if self.carry_over && previous.particle_count() > 0 {
+            self.retired.push(previous);
+        } else {
+            self.retired.clear();
+        }
+        self.mode = Some(mode);
+    }
+
+    /// Removes every particle, including those of retired simulations.
+    pub fn clear(&mut self) {
+        self.state.reset();
+        self.retired.clear();
+    }
+
+    /// Multiplies the speed of time by `factor`, within [`MIN_SPEED`] and
+    /// [`MAX_SPEED`].
+    pub fn scale_speed(&mut self, factor: f32) {
+        self.speed = (self.speed * factor).clamp(MIN_SPEED, MAX_SPEED);
     }
 
     /// Set running to false to quit the application.
@@ -73,30 +347,52 @@ impl App {
         self.area
     }
 
+    /// Moves the simulation to `area`, letting it adapt if the size
+    /// changed, and spawns the first burst if it is
+    /// [due](Self::spawn_on_start). See [`Simulation::resize`].
     pub fn set_area(&mut self, area: Rect) {
-        self.area = area;
+        if area != self.area {
+            self.state.resize(self.area, area, self.remap_on_resize);
+            for retired in &mut self.retired {
+                retired.resize(self.area, area, self.remap_on_resize);
+            }
+            self.area = area;
+        }
+        if self.spawn_on_start && !area.is_empty() {
+            self.spawn_on_start = false;
+            self.spawn_particles();
+        }
     }
 
     pub fn spawn_particles(&mut self) {
-        match &mut self.state {
-            AppSimulation::Confetti(state) => {
-                // Handle Confetti state particles
-                self.num_particles += state.spawn_particles(self.area.width as usize);
-            }
-            AppSimulation::Fireworks(state) => {
-                // Handle Fireworks state particles
-                self.num_particles +=
-                    state.spawn_particles(self.area.width as usize, self.area.height as usize);
-            }
-            AppSimulation::ShootingStar(state) => {
-                // Handle Fireworks state particles
-                self.num_particles +=
-                    state.spawn_particles(self.area.width as usize, self.area.height as usize);
-            }
-        }
+        self.num_particles += self.state.spawn_particles(self.area);
+    }
+
+    /// Spawns a burst aimed at `target`, relative to the area.
+    pub fn spawn_at(&mut self, target: Point2<f32>) {
+        self.num_particles += self.state.spawn_at(self.area, target);
+    }
+
+    /// Converts a terminal cell to the position of its centre relative to
+    /// the area, or `None` if the cell lies outside it.
+    pub fn cell_to_point(&self, column: u16, row: u16) -> Option<Point2<f32>> {
+        let cell = Rect::new(column, row, 1, 1);
+        self.area.intersects(cell).then(|| {
+            Point2::new(
+                f32::from(column - self.area.x) + 0.5,
+                f32::from(row - self.area.y) + 0.5,
+            )
+        })
+    }
+
+    /// Multiplies the spawn intensity by `factor`, within
+    /// [`MIN_INTENSITY`] and [`MAX_INTENSITY`].
+    pub fn scale_intensity(&mut self, factor: f32) {
+        self.intensity = (self.intensity * factor).clamp(MIN_INTENSITY, MAX_INTENSITY);
+        self.state.set_intensity(self.intensity);
     }
 
-    pub fn get_simulation_state(&mut self) -> &mut AppSimulation {
+    pub fn get_simulation_state(&mut self) -> &mut Box<dyn Simulation> {
         &mut self.state
     }
 }
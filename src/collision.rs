@@ -0,0 +1,260 @@
+use crate::projectile::Projectile;
+use nalgebra::{Point2, Vector2};
+use ratatui::layout::Rect;
+
+/// Smallest side of a grid cell, so tiny radii do not blow up the grid.
+const MIN_CELL_SIZE: f32 = 0.5;
+
+/// A uniform grid over an area that buckets points by the cell they fall
+/// in, so that points close to each other can be found without comparing
+/// every pair.
+///
+/// Points outside the area are put in the nearest border cell.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpatialHash {
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    /// Index into `entries` of the first point of every cell, plus one past
+    /// the end.
+    starts: Vec<usize>,
+    /// Indices of the points, grouped by cell.
+    entries: Vec<usize>,
+}
+
+impl SpatialHash {
+    /// Buckets `points`, relative to the top-left corner of `area`, into
+    /// cells at least `cell_size` wide.
+    pub fn new(area: Rect, cell_size: f32, points: &[Point2<f32>]) -> Self {
+        let cell_size = cell_size.max(MIN_CELL_SIZE);
+        let columns = (f32::from(area.width) / cell_size).ceil().max(1.0) as usize;
+        let rows = (f32::from(area.height) / cell_size).ceil().max(1.0) as usize;
+        let mut hash = Self {
+            cell_size,
+            columns,
+            rows,
+            starts: vec![0; columns * rows + 1],
+            entries: vec![0; points.len()],
+        };
+
+        // Counting sort: size every cell, then place every point.
+        let cells: Vec<usize> = points.iter().map(|&point| hash.cell(point)).collect();
+        for &cell in &cells {
+            hash.starts[cell + 1] += 1;
+        }
+        for cell in 0..columns * rows {
+            hash.starts[cell + 1] += hash.starts[cell];
+        }
+        let mut next = hash.starts.clone();
+        for (index, &cell) in cells.iter().enumerate() {
+            hash.entries[next[cell]] = index;
+            next[cell] += 1;
+        }
+        hash
+    }
+
+    /// Returns the index of the cell `point` falls in.
+    fn cell(&self, point: Point2<f32>) -> usize {
+        let clamp =
+            |value: f32, cells: usize| ((value / self.cell_size).max(0.0) as usize).min(cells - 1);
+        clamp(point.y, self.rows) * self.columns + clamp(point.x, self.columns)
+    }
+
+    /// Returns the indices of the points in the cell at `column` and `row`.
+    fn bucket(&self, column: usize, row: usize) -> &[usize] {
+        let cell = row * self.columns + column;
+        &self.entries[self.starts[cell]..self.starts[cell + 1]]
+    }
+
+    /// Calls `f` once for every pair of points in the same or adjacent
+    /// cells, which includes every pair closer than the cell size.
+    pub fn for_each_pair(&self, mut f: impl FnMut(usize, usize)) {
+        // Looking right and down only visits every pair of cells once.
+        const NEIGHBOURS: [(isize, isize); 4] = [(1, 0), (-1, 1), (0, 1), (1, 1)];
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let bucket = self.bucket(column, row);
+                for (i, &a) in bucket.iter().enumerate() {
+                    for &b in &bucket[i + 1..] {
+                        f(a, b);
+                    }
+                }
+                for (dx, dy) in NEIGHBOURS {
+                    let (Some(x), Some(y)) = (
+                        column.checked_add_signed(dx).filter(|&x| x < self.columns),
+                        row.checked_add_signed(dy).filter(|&y| y < self.rows),
+                    ) else {
+                        continue;
+                    };
+                    let neighbour = self.bucket(x, y);
+                    for &a in bucket {
+                        for &b in neighbour {
+                            f(a, b);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// How particles bounce off one another. Every particle is a disc of the
+/// same size and mass.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Collisions {
+    /// Radius of every particle, in cells.
+    pub radius: f32,
+    /// Share of their approach speed two particles part with: `1.0` is
+    /// perfectly elastic, `0.0` perfectly inelastic.
+    pub restitution: f32,
+}
+
+impl Default for Collisions {
+    fn default() -> Self {
+        Self {
+            radius: 0.5,
+            restitution: 0.5,
+        }
+    }
+}
+
+impl Collisions {
+    /// Separates and bounces every pair of overlapping particles in `area`
+    /// that is moving closer together, returning how many pairs collided.
+    ///
+    /// Pairs that already move apart are left alone, so a burst spawned at a
+    /// single point spreads out on its own.
+    pub fn resolve<T>(
+        &self,
+        area: Rect,
+        particles: &mut [T],
+        physics: impl Fn(&mut T) -> &mut Projectile,
+    ) -> usize {
+        let mut bodies: Vec<(Point2<f32>, Vector2<f32>)> = particles
+            .iter_mut()
+            .map(|particle| {
+                let physics = physics(particle);
+                (physics.position(), physics.velocity())
+            })
+            .collect();
+        let positions: Vec<Point2<f32>> = bodies.iter().map(|(pos, _)| *pos).collect();
+        let diameter = 2.0 * self.radius;
+        let hash = SpatialHash::new(area, diameter, &positions);
+
+        let mut contacts = 0;
+        hash.for_each_pair(|a, b| {
+            let offset = bodies[b].0 - bodies[a].0;
+            let distance = offset.norm();
+            if distance >= diameter || distance <= f32::EPSILON {
+                return;
+            }
+            let normal = offset / distance;
+            let approach = (bodies[b].1 - bodies[a].1).dot(&normal);
+            if approach >= 0.0 {
+                return;
+            }
+            // Equal masses share the impulse and the overlap evenly.
+            let impulse = normal * (-(1.0 + self.restitution) * approach / 2.0);
+            let push = normal * ((diameter - distance) / 2.0);
+            bodies[a].1 -= impulse;
+            bodies[b].1 += impulse;
+            bodies[a].0 -= push;
+            bodies[b].0 += push;
+            contacts += 1;
+        });
+
+        for (particle, (pos, vel)) in particles.iter_mut().zip(bodies) {
+            let physics = physics(particle);
+            physics.set_position(pos);
+            physics.set_velocity(vel);
+        }
+        contacts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck::quickcheck;
+
+    fn head_on(restitution: f32) -> [Projectile; 2] {
+        let mut bodies = [
+            Projectile::new(
+                Point2::new(4.6, 2.0),
+                Vector2::new(3.0, 0.0),
+                Vector2::zeros(),
+            ),
+            Projectile::new(
+                Point2::new(5.4, 2.0),
+                Vector2::new(-1.0, 0.0),
+                Vector2::zeros(),
+            ),
+        ];
+        let collisions = Collisions {
+            radius: 0.5,
+            restitution,
+        };
+        let contacts = collisions.resolve(Rect::new(0, 0, 10, 4), &mut bodies, |body| body);
+        assert_eq!(contacts, 1);
+        bodies
+    }
+
+    #[test]
+    fn elastic_collisions_swap_velocities() {
+        let [a, b] = head_on(1.0);
+        assert_eq!(a.velocity(), Vector2::new(-1.0, 0.0));
+        assert_eq!(b.velocity(), Vector2::new(3.0, 0.0));
+        assert!((b.position() - a.position()).norm() >= 1.0 - 1e-5);
+    }
+
+    #[test]
+    fn inelastic_collisions_keep_momentum_and_lose_energy() {
+        let [a, b] = head_on(0.0);
+        assert_eq!(a.velocity(), b.velocity());
+        assert_eq!(a.velocity() + b.velocity(), Vector2::new(2.0, 0.0));
+
+        let [a, b] = head_on(0.5);
+        assert_eq!(b.velocity().x - a.velocity().x, 2.0);
+    }
+
+    #[test]
+    fn separating_particles_are_left_alone() {
+        let mut bodies = [
+            Projectile::new(
+                Point2::new(5.0, 2.0),
+                Vector2::new(-1.0, 0.0),
+                Vector2::zeros(),
+            ),
+            Projectile::new(
+                Point2::new(5.5, 2.0),
+                Vector2::new(1.0, 0.0),
+                Vector2::zeros(),
+            ),
+        ];
+        let contacts = Collisions::default().resolve(Rect::new(0, 0, 10, 4), &mut bodies, |b| b);
+        assert_eq!(contacts, 0);
+        assert_eq!(bodies[0].position(), Point2::new(5.0, 2.0));
+    }
+
+    quickcheck! {
+        fn spatial_hash_finds_every_close_pair(coords: Vec<(u8, u8)>) -> bool {
+            // Spread points a little past the area to cover clamping too.
+            let points: Vec<Point2<f32>> = coords
+                .iter()
+                .map(|&(x, y)| Point2::new(f32::from(x) / 8.0 - 2.0, f32::from(y) / 16.0 - 2.0))
+                .collect();
+            let hash = SpatialHash::new(Rect::new(0, 0, 24, 10), 1.0, &points);
+            let mut found = vec![];
+            hash.for_each_pair(|a, b| found.push((a.min(b), a.max(b))));
+            found.sort_unstable();
+
+            let no_duplicates = found.windows(2).all(|pair| pair[0] != pair[1]);
+            let close_pairs_found = (0..points.len()).all(|a| {
+                (a + 1..points.len())
+                    .filter(|&b| (points[b] - points[a]).norm() < 1.0)
+                    .all(|b| found.binary_search(&(a, b)).is_ok())
+            });
+            no_duplicates && close_pairs_found
+        }
+    }
+}
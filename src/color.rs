@@ -0,0 +1,240 @@
+use ratatui::style::Color;
+
+/// Returns the red, green and blue channels of `color`, if it is an RGB
+/// colour.
+pub fn rgb(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Rgb(r, g, b) => Some((r, g, b)),
+        _ => None,
+    }
+}
+
+/// Parses a `#rrggbb` hex colour.
+pub fn parse_hex(s: &str) -> Option<Color> {
+    let hex = s.trim().strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// Linearly interpolates between two RGB colours in sRGB space; `t = 0.0`
+/// gives `from` and `t = 1.0` gives `to`.
+///
+/// Colours that are not RGB cannot be blended, so `to` is returned once `t`
+/// reaches one half and `from` before that.
+pub fn lerp_rgb(from: Color, to: Color, t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    match (rgb(from), rgb(to)) {
+        (Some((r0, g0, b0)), Some((r1, g1, b1))) => {
+            let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+            Color::Rgb(mix(r0, r1), mix(g0, g1), mix(b0, b1))
+        }
+        _ if t < 0.5 => from,
+        _ => to,
+    }
+}
+
+/// Interpolates between two RGB colours in the OKLab colour space, which
+/// keeps perceived brightness even across the blend, unlike sRGB.
+///
+/// Colours that are not RGB are handled as in [`lerp_rgb`].
+pub fn lerp_oklab(from: Color, to: Color, t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    if t == 0.0 {
+        return from;
+    }
+    match (rgb(from), rgb(to)) {
+        (Some(a), Some(b)) => {
+            let a = to_oklab(a);
+            let b = to_oklab(b);
+            from_oklab([
+                a[0] + (b[0] - a[0]) * t,
+                a[1] + (b[1] - a[1]) * t,
+                a[2] + (b[2] - a[2]) * t,
+            ])
+        }
+        _ => lerp_rgb(from, to, t),
+    }
+}
+
+/// Samples a gradient running through every colour of `palette` in order,
+/// interpolated in OKLab; `t = 0.0` is the first colour, `t = 1.0` the last.
+pub fn gradient(palette: &[Color], t: f32) -> Color {
+    match palette.len() {
+        0 => Color::Reset,
+        1 => palette[0],
+        n => {
+            let position = t.clamp(0.0, 1.0) * (n - 1) as f32;
+            let index = (position.floor() as usize).min(n - 2);
+            lerp_oklab(palette[index], palette[index + 1], position - index as f32)
+        }
+    }
+}
+
+/// The 16 standard ANSI colours with their usual xterm values.
+const ANSI_16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Channel values of the 6x6x6 colour cube of the ANSI-256 palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance((r0, g0, b0): (u8, u8, u8), (r1, g1, b1): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (i32::from(a) - i32::from(b)).pow(2) as u32;
+    d(r0, r1) + d(g0, g1) + d(b0, b1)
+}
+
+/// Returns the closest colour of the ANSI-256 palette to an RGB colour,
+/// picking from its colour cube and grey ramp. Other colours are returned
+/// unchanged.
+pub fn to_ansi256(color: Color) -> Color {
+    let Some((r, g, b)) = rgb(color) else {
+        return color;
+    };
+    let level = |c: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| CUBE_LEVELS[i].abs_diff(c))
+            .unwrap_or(0)
+    };
+    let (ri, gi, bi) = (level(r), level(g), level(b));
+    let cube = (CUBE_LEVELS[ri], CUBE_LEVELS[gi], CUBE_LEVELS[bi]);
+
+    let mean = (u32::from(r) + u32::from(g) + u32::from(b)) / 3;
+    let grey_index = (mean.saturating_sub(3) / 10).min(23) as u8;
+    let grey_level = 8 + 10 * grey_index;
+    let grey = (grey_level, grey_level, grey_level);
+
+    if distance(grey, (r, g, b)) < distance(cube, (r, g, b)) {
+        Color::Indexed(232 + grey_index)
+    } else {
+        Color::Indexed(16 + 36 * ri as u8 + 6 * gi as u8 + bi as u8)
+    }
+}
+
+/// Returns the closest of the 16 standard ANSI colours to an RGB colour.
+/// Other colours are returned unchanged.
+pub fn to_ansi16(color: Color) -> Color {
+    let Some(target) = rgb(color) else {
+        return color;
+    };
+    ANSI_16
+        .iter()
+        .min_by_key(|(_, value)| distance(*value, target))
+        .map_or(color, |(ansi, _)| *ansi)
+}
+
+fn srgb_to_linear(c: u8) -> f32 {
+    let c = c as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f32) -> u8 {
+    let c = c.clamp(0.0, 1.0);
+    let c = if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (c * 255.0).round() as u8
+}
+
+fn to_oklab((r, g, b): (u8, u8, u8)) -> [f32; 3] {
+    let (r, g, b) = (srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b));
+    let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+    let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+    let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+    [
+        0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+        1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+        0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+    ]
+}
+
+fn from_oklab([l, a, b]: [f32; 3]) -> Color {
+    let l_ = (l + 0.396_337_78 * a + 0.215_803_76 * b).powi(3);
+    let m_ = (l - 0.105_561_346 * a - 0.063_854_17 * b).powi(3);
+    let s_ = (l - 0.089_484_18 * a - 1.291_485_5 * b).powi(3);
+    Color::Rgb(
+        linear_to_srgb(4.076_741_7 * l_ - 3.307_711_6 * m_ + 0.230_969_94 * s_),
+        linear_to_srgb(-1.268_438 * l_ + 2.609_757_4 * m_ - 0.341_319_38 * s_),
+        linear_to_srgb(-0.004_196_086_3 * l_ - 0.703_418_6 * m_ + 1.707_614_7 * s_),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hex_colours() {
+        assert_eq!(parse_hex("#a864fd"), Some(Color::Rgb(168, 100, 253)));
+        assert_eq!(parse_hex(" #FDFF6A "), Some(Color::Rgb(253, 255, 106)));
+        assert_eq!(parse_hex("a864fd"), None);
+        assert_eq!(parse_hex("#a864f"), None);
+        assert_eq!(parse_hex("#gg0000"), None);
+    }
+
+    #[test]
+    fn downgrades_to_the_nearest_ansi_colour() {
+        assert_eq!(to_ansi256(Color::Rgb(255, 0, 0)), Color::Indexed(196));
+        assert_eq!(to_ansi256(Color::Rgb(95, 135, 175)), Color::Indexed(67));
+        assert_eq!(to_ansi256(Color::Rgb(128, 128, 128)), Color::Indexed(244));
+        assert_eq!(to_ansi256(Color::Red), Color::Red);
+
+        assert_eq!(to_ansi16(Color::Rgb(250, 10, 10)), Color::LightRed);
+        assert_eq!(to_ansi16(Color::Rgb(120, 125, 130)), Color::DarkGray);
+        assert_eq!(to_ansi16(Color::Indexed(42)), Color::Indexed(42));
+    }
+
+    #[test]
+    fn oklab_round_trips_rgb() {
+        for color in [(0, 0, 0), (255, 255, 255), (168, 100, 253), (41, 205, 255)] {
+            assert_eq!(
+                from_oklab(to_oklab(color)),
+                Color::Rgb(color.0, color.1, color.2)
+            );
+        }
+    }
+
+    #[test]
+    fn lerp_oklab_hits_both_ends() {
+        let from = Color::Rgb(255, 113, 141);
+        let to = Color::Rgb(0, 0, 0);
+        assert_eq!(lerp_oklab(from, to, 0.0), from);
+        assert_eq!(lerp_oklab(from, to, 1.0), to);
+        assert_eq!(lerp_oklab(Color::Red, to, 0.2), Color::Red);
+    }
+
+    #[test]
+    fn gradient_passes_through_every_stop() {
+        let palette = [
+            Color::Rgb(255, 0, 0),
+            Color::Rgb(0, 255, 0),
+            Color::Rgb(0, 0, 255),
+        ];
+        assert_eq!(gradient(&palette, 0.0), palette[0]);
+        assert_eq!(gradient(&palette, 0.5), palette[1]);
+        assert_eq!(gradient(&palette, 1.0), palette[2]);
+    }
+}
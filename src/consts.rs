@@ -1,5 +1,6 @@
 use nalgebra::Vector2;
 use ratatui::prelude::Color;
+use std::time::Duration;
 
 pub const TICK_RATE_IN_MILI: u64 = 33;
 
@@ -7,8 +8,35 @@ pub const TICK_RATE_IN_MILI: u64 = 33;
 #[allow(dead_code)]
 pub const FRAMES_PER_SECOND: f32 = 1000.0 / TICK_RATE_IN_MILI as f32;
 
+/// Length of one physics step. The simulation always advances in steps of this
+/// size, however irregularly ticks arrive.
 #[allow(dead_code)]
-pub const NUM_PARTICLES: usize = 75;
+pub const FIXED_TIMESTEP: Duration = Duration::from_millis(TICK_RATE_IN_MILI);
+
+/// Upper bound on the time simulated per tick, so a stalled terminal does not
+/// cause a burst of catch-up steps.
+#[allow(dead_code)]
+pub const MAX_FRAME_TIME: Duration = Duration::from_millis(250);
+
+/// Factor one scroll step multiplies or divides the spawn intensity by.
+#[allow(dead_code)]
+pub const INTENSITY_STEP: f32 = 1.25;
+
+/// Bounds on the spawn intensity.
+#[allow(dead_code)]
+pub const MIN_INTENSITY: f32 = 0.1;
+#[allow(dead_code)]
+pub const MAX_INTENSITY: f32 = 10.0;
+
+/// Factor one key press speeds time up or slows it down by.
+#[allow(dead_code)]
+pub const SPEED_STEP: f32 = 2.0;
+
+/// Bounds on the speed of simulated time.
+#[allow(dead_code)]
+pub const MIN_SPEED: f32 = 0.125;
+#[allow(dead_code)]
+pub const MAX_SPEED: f32 = 8.0;
 
 #[allow(dead_code)]
 pub const CHARACTERS: [char; 6] = ['█', '▓', '▒', '░', '▄', '▀'];
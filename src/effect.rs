@@ -0,0 +1,1376 @@
+use crate::app::Mode;
+use crate::collision::Collisions;
+use crate::color::{self, parse_hex};
+use crate::emitter::{scaled, Emitter, Rate, Shape, Velocity};
+use crate::events::{self, SubEmitter, Trigger};
+use crate::forces::{Force, ForceField};
+use crate::glyphs::GlyphSet;
+use crate::lifetime::{self, Lifetime};
+use crate::palette::Palette;
+use crate::projectile::{Integrator, Projectile};
+use crate::render::RenderOptions;
+use crate::simulation::{self, simulation_widget, Body, ParticleSystem, Simulation};
+use nalgebra::{Point2, Vector2};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use ratatui::prelude::*;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+use std::{error, fs, io};
+
+/// Effects bundled with the crate, by name.
+pub const BUILTIN_EFFECTS: [(&str, &str); 4] = [
+    ("confetti", include_str!("../effects/confetti.toml")),
+    ("fireworks", include_str!("../effects/fireworks.toml")),
+    ("stars", include_str!("../effects/stars.toml")),
+    ("crackle", include_str!("../effects/crackle.toml")),
+];
+
+/// Longest lifetime a particle type may have, in seconds.
+const MAX_LIFETIME: f32 = 3600.0;
+
+/// Most particles per second a continuous emitter or pointer may stream.
+const MAX_RATE: f32 = 10_000.0;
+
+/// Most particles a burst or a sub-emitter may spawn at once.
+const MAX_COUNT: usize = 10_000;
+
+/// A `[min, max]` range a value is drawn uniformly from.
+pub type Interval = [f32; 2];
+
+fn sample(rng: &mut impl Rng, [min, max]: Interval) -> f32 {
+    min + (max - min) * rng.gen::<f32>()
+}
+
+/// Error raised while loading an effect definition.
+#[derive(Debug)]
+pub enum EffectError {
+    /// The definition file could not be read.
+    Io(io::Error),
+    /// The definition is not valid TOML or does not match the format.
+    Parse(toml::de::Error),
+    /// The definition parsed but is inconsistent, e.g. it refers to a
+    /// particle type that is not defined.
+    Invalid(String),
+}
+
+impl fmt::Display for EffectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EffectError::Io(e) => write!(f, "cannot read effect: {e}"),
+            EffectError::Parse(e) => write!(f, "cannot parse effect: {e}"),
+            EffectError::Invalid(reason) => write!(f, "invalid effect: {reason}"),
+        }
+    }
+}
+
+impl error::Error for EffectError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            EffectError::Io(e) => Some(e),
+            EffectError::Parse(e) => Some(e),
+            EffectError::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for EffectError {
+    fn from(e: io::Error) -> Self {
+        EffectError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for EffectError {
+    fn from(e: toml::de::Error) -> Self {
+        EffectError::Parse(e)
+    }
+}
+
+/// A particle effect described as data: what emits particles, how they move
+/// and what they look like.
+///
+/// Definitions are written in TOML; see the files under `effects/` for the
+/// built-in effects.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EffectDefinition {
+    pub name: String,
+    /// Built-in simulation whose variant of the glyph set draws the particle
+    /// types that take their [`glyphs`](ParticleDefinition::glyphs) from it.
+    #[serde(default)]
+    pub style: Mode,
+    /// Acceleration applied to every particle, in cells per second squared.
+    #[serde(default = "default_gravity")]
+    pub gravity: [f32; 2],
+    /// Forces applied to every particle in addition to gravity.
+    #[serde(default)]
+    pub forces: Vec<ForceDefinition>,
+    /// What is spawned by every call to
+    /// [`spawn_particles`](Simulation::spawn_particles).
+    pub emitters: Vec<EmitterDefinition>,
+    /// Particle types, by name.
+    pub particles: BTreeMap<String, ParticleDefinition>,
+}
+
+fn default_gravity() -> [f32; 2] {
+    [0.0, 9.81]
+}
+
+fn one() -> usize {
+    1
+}
+
+/// A [`Force`] as written in a definition.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum ForceDefinition {
+    LinearDrag {
+        k: f32,
+    },
+    QuadraticDrag {
+        k: f32,
+    },
+    Wind {
+        velocity: [f32; 2],
+    },
+    /// `position` is given in cells from the top-left of the area.
+    Attractor {
+        position: [f32; 2],
+        strength: f32,
+        radius: f32,
+    },
+    Turbulence {
+        strength: f32,
+        scale: f32,
+        speed: f32,
+    },
+}
+
+impl ForceDefinition {
+    /// Returns whether every parameter of the force is finite.
+    pub fn is_finite(&self) -> bool {
+        let values = match *self {
+            ForceDefinition::LinearDrag { k } | ForceDefinition::QuadraticDrag { k } => vec![k],
+            ForceDefinition::Wind { velocity } => velocity.to_vec(),
+            ForceDefinition::Attractor {
+                position,
+                strength,
+                radius,
+            } => vec![position[0], position[1], strength, radius],
+            ForceDefinition::Turbulence {
+                strength,
+                scale,
+                speed,
+            } => vec![strength, scale, speed],
+        };
+        values.iter().all(|v| v.is_finite())
+    }
+}
+
+impl From<ForceDefinition> for Force {
+    fn from(force: ForceDefinition) -> Self {
+        match force {
+            ForceDefinition::LinearDrag { k } => Force::LinearDrag(k),
+            ForceDefinition::QuadraticDrag { k } => Force::QuadraticDrag(k),
+            ForceDefinition::Wind { velocity: [x, y] } => Force::Wind(Vector2::new(x, y)),
+            ForceDefinition::Attractor {
+                position: [x, y],
+                strength,
+                radius,
+            } => Force::Attractor {
+                position: Point2::new(x, y),
+                strength,
+                radius,
+            },
+            ForceDefinition::Turbulence {
+                strength,
+                scale,
+                speed,
+            } => Force::Turbulence {
+                strength,
+                scale,
+                speed,
+            },
+        }
+    }
+}
+
+/// Spawns particles of type `particle`, as an [`Emitter`] does.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EmitterDefinition {
+    pub particle: String,
+    #[serde(default)]
+    pub rate: Rate,
+    #[serde(default)]
+    pub shape: Shape,
+    pub velocity: Velocity,
+    /// How the emitter spawns where the mouse is clicked or dragged.
+    #[serde(default)]
+    pub pointer: PointerDefinition,
+}
+
+impl EmitterDefinition {
+    /// Returns the [`Emitter`] this definition describes.
+    pub fn emitter(&self) -> Emitter {
+        Emitter::new(self.shape, self.velocity).with_rate(self.rate)
+    }
+}
+
+/// How an emitter spawns at the mouse. A click spawns once, and dragging
+/// streams `rate` spawns per second.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum PointerDefinition {
+    /// A burst the size of the emitter's at the cursor, moving at
+    /// `velocity`, or at the emitter's velocity. Streams two bursts' worth
+    /// per second unless `rate` says otherwise.
+    At {
+        #[serde(default)]
+        velocity: Option<Velocity>,
+        #[serde(default)]
+        rate: Option<f32>,
+    },
+    /// One particle from the emitter's shape, aimed at the cursor at the
+    /// speed it would have had.
+    Aim { rate: f32 },
+    /// One particle from the bottom row below the cursor, just fast enough
+    /// to climb to it against gravity. It fires its apex sub-emitters once
+    /// it gets there.
+    Launch { rate: f32 },
+}
+
+impl Default for PointerDefinition {
+    fn default() -> Self {
+        PointerDefinition::At {
+            velocity: None,
+            rate: None,
+        }
+    }
+}
+
+/// Characters of the glyph set a particle type can be drawn with, so that
+/// `--glyphs` and `--ascii` apply to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GlyphRole {
+    /// One of the confetti pieces, picked at random per particle.
+    Pieces,
+    /// The head of a rocket or shooting star.
+    Head,
+    /// The spark characters, burnt through over the particle's life unless
+    /// it has its own `ramp`.
+    Sparks,
+}
+
+/// The look and behaviour of one particle type.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParticleDefinition {
+    /// Characters a particle is drawn with, one picked at random per
+    /// particle. Required unless the particle type uses `glyphs`.
+    #[serde(default)]
+    pub chars: String,
+    /// Draw the particle with these characters of the glyph set instead of
+    /// `chars`.
+    #[serde(default)]
+    pub glyphs: Option<GlyphRole>,
+    /// Characters a mortal particle burns through over its life, replacing
+    /// `chars` once it is spawned.
+    #[serde(default)]
+    pub ramp: Option<String>,
+    /// `#rrggbb` colours picked at random per particle. Defaults to the
+    /// built-in palette.
+    #[serde(default)]
+    pub palette: Option<Vec<String>>,
+    /// `#rrggbb` colours a mortal particle runs through over its life,
+    /// blended in OKLab, starting from the first instead of one from the
+    /// palette.
+    #[serde(default)]
+    pub gradient: Option<Vec<String>>,
+    /// Take the colour of the emitting particle instead of the palette.
+    #[serde(default)]
+    pub inherit_color: bool,
+    /// Lifespan in seconds, at most an hour. Without one, particles only die
+    /// by leaving the area or triggering a sub-emitter.
+    #[serde(default)]
+    pub lifetime: Option<Interval>,
+    #[serde(default)]
+    pub tail: Option<TailDefinition>,
+    #[serde(default)]
+    pub sub_emitters: Vec<SubEmitterDefinition>,
+}
+
+/// A streak drawn behind a moving particle.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TailDefinition {
+    /// Defaults to the tail of the glyph set.
+    #[serde(default)]
+    pub char: Option<char>,
+    /// How many seconds of motion the tail covers.
+    pub length: f32,
+}
+
+/// Spawns `count` particles of type `particle` where a particle triggers
+/// it. The emitting particle is removed.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SubEmitterDefinition {
+    pub trigger: Trigger,
+    pub particle: String,
+    #[serde(default = "one")]
+    pub count: usize,
+    pub velocity: Velocity,
+}
+
+impl EffectDefinition {
+    /// Parses and validates a TOML definition.
+    pub fn from_toml(source: &str) -> Result<Self, EffectError> {
+        let definition: Self = toml::from_str(source)?;
+        definition.validate()?;
+        Ok(definition)
+    }
+
+    /// Reads a TOML definition from `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, EffectError> {
+        Self::from_toml(&fs::read_to_string(path)?)
+    }
+
+    /// Returns the bundled effect called `name`.
+    pub fn builtin(name: &str) -> Option<Self> {
+        BUILTIN_EFFECTS
+            .iter()
+            .find(|(builtin, _)| *builtin == name)
+            .map(|(_, source)| Self::from_toml(source).expect("built-in effects are valid"))
+    }
+
+    /// Loads `name_or_path` as a definition file if it exists, and as the
+    /// name of a bundled effect otherwise.
+    pub fn resolve(name_or_path: &str) -> Result<Self, EffectError> {
+        if Path::new(name_or_path).exists() {
+            return Self::load(name_or_path);
+        }
+        Self::builtin(name_or_path).ok_or_else(|| {
+            let names: Vec<_> = BUILTIN_EFFECTS.iter().map(|(name, _)| *name).collect();
+            EffectError::Invalid(format!(
+                "no effect file or built-in effect named `{name_or_path}` (built-in: {})",
+                names.join(", ")
+            ))
+        })
+    }
+
+    /// Checks that every particle type referred to is defined, every
+    /// particle type can be drawn, every number is finite, every lifetime,
+    /// tail, rate and count is in range, and no particle type spawns itself
+    /// through its sub-emitters.
+    pub fn validate(&self) -> Result<(), EffectError> {
+        let check_ref = |name: &str| {
+            if self.particles.contains_key(name) {
+                Ok(())
+            } else {
+                Err(EffectError::Invalid(format!(
+                    "unknown particle type `{name}`"
+                )))
+            }
+        };
+        let invalid = |reason: String| Err(EffectError::Invalid(reason));
+        if !self.gravity.iter().all(|v| v.is_finite()) {
+            return invalid(format!("gravity {:?} is not finite", self.gravity));
+        }
+        if let Some(force) = self.forces.iter().find(|force| !force.is_finite()) {
+            return invalid(format!(
+                "force {force:?} has a parameter that is not finite"
+            ));
+        }
+        for emitter in &self.emitters {
+            check_ref(&emitter.particle)?;
+            let name = &emitter.particle;
+            let pointer_velocity = match emitter.pointer {
+                PointerDefinition::At { velocity, .. } => velocity,
+                PointerDefinition::Aim { .. } | PointerDefinition::Launch { .. } => None,
+            };
+            if !emitter.shape.is_finite()
+                || !emitter.velocity.is_finite()
+                || !pointer_velocity.is_none_or(|velocity| velocity.is_finite())
+            {
+                return invalid(format!(
+                    "emitter of `{name}` has a shape or velocity that is not finite"
+                ));
+            }
+            let rates = [
+                match emitter.rate {
+                    Rate::Continuous(rate) => Some(rate),
+                    Rate::Burst(_) => None,
+                },
+                match emitter.pointer {
+                    PointerDefinition::At { rate, .. } => rate,
+                    PointerDefinition::Aim { rate } | PointerDefinition::Launch { rate } => {
+                        Some(rate)
+                    }
+                },
+            ];
+            // Also rejects NaN, which fails every comparison.
+            if let Some(rate) = rates
+                .into_iter()
+                .flatten()
+                .find(|rate| !(0.0..=MAX_RATE).contains(rate))
+            {
+                return invalid(format!(
+                    "emitter of `{name}` has rate {rate}, expected 0 to {MAX_RATE} per second"
+                ));
+            }
+            if emitter.emitter().burst_size() > MAX_COUNT {
+                return invalid(format!(
+                    "emitter of `{name}` bursts {} particles, expected at most {MAX_COUNT}",
+                    emitter.emitter().burst_size()
+                ));
+            }
+        }
+        for (name, particle) in &self.particles {
+            if particle.chars.is_empty() && particle.glyphs.is_none() {
+                return invalid(format!("particle type `{name}` has no chars"));
+            }
+            let colors = particle.palette.iter().chain(&particle.gradient).flatten();
+            for color in colors {
+                if parse_hex(color).is_none() {
+                    return invalid(format!(
+                        "particle type `{name}` has invalid colour `{color}`"
+                    ));
+                }
+            }
+            if let Some(tail) = particle.tail {
+                if !(tail.length.is_finite() && tail.length >= 0.0) {
+                    return invalid(format!(
+                        "particle type `{name}` has tail length {}, expected a \
+                         non-negative number of seconds",
+                        tail.length
+                    ));
+                }
+            }
+            if let Some([min, max]) = particle.lifetime {
+                if !(0.0..=MAX_LIFETIME).contains(&min) || !(min..=MAX_LIFETIME).contains(&max) {
+                    return invalid(format!(
+                        "particle type `{name}` has lifetime [{min}, {max}], expected \
+                         0 <= min <= max <= {MAX_LIFETIME} seconds"
+                    ));
+                }
+            }
+            for sub_emitter in &particle.sub_emitters {
+                check_ref(&sub_emitter.particle)?;
+                let threshold = match sub_emitter.trigger {
+                    Trigger::Apex { threshold } => threshold,
+                    Trigger::Death | Trigger::Expire | Trigger::Boundary => 0.0,
+                };
+                if !threshold.is_finite() || !sub_emitter.velocity.is_finite() {
+                    return invalid(format!(
+                        "sub-emitter of `{name}` has a trigger or velocity that is not finite"
+                    ));
+                }
+                if sub_emitter.count > MAX_COUNT {
+                    return invalid(format!(
+                        "sub-emitter of `{name}` spawns {} particles, expected at most \
+                         {MAX_COUNT}",
+                        sub_emitter.count
+                    ));
+                }
+            }
+        }
+        if let Some(name) = self.find_cycle() {
+            return invalid(format!(
+                "particle type `{name}` spawns itself through its sub-emitters, so it \
+                 would multiply forever"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns a particle type whose sub-emitters, directly or through
+    /// other types, spawn particles of that same type.
+    fn find_cycle(&self) -> Option<&str> {
+        // `false` while a type is being visited, `true` once every type it
+        // leads to is known to end.
+        fn visit<'a>(
+            definition: &'a EffectDefinition,
+            name: &'a str,
+            visited: &mut BTreeMap<&'a str, bool>,
+        ) -> Option<&'a str> {
+            match visited.get(name) {
+                Some(true) => return None,
+                Some(false) => return Some(name),
+                None => {}
+            }
+            visited.insert(name, false);
+            for sub_emitter in &definition.particles[name].sub_emitters {
+                if let Some(cycle) = visit(definition, &sub_emitter.particle, visited) {
+                    return Some(cycle);
+                }
+            }
+            visited.insert(name, true);
+            None
+        }
+
+        let mut visited = BTreeMap::new();
+        self.particles
+            .keys()
+            .find_map(|name| visit(self, name, &mut visited))
+    }
+}
+
+/// A [`ParticleDefinition`] with its names resolved, ready to spawn.
+#[derive(Debug, Clone)]
+struct Kind {
+    chars: Vec<char>,
+    glyphs: Option<GlyphRole>,
+    ramp: Vec<char>,
+    /// Colours of the particle type, or `None` to use the palette of the
+    /// simulation.
+    palette: Option<Vec<Color>>,
+    /// Colours the particle type runs through over its life, if any.
+    gradient: Vec<Color>,
+    inherit_color: bool,
+    lifetime: Option<Interval>,
+    tail: Option<TailDefinition>,
+    /// Sub-emitters with the type of particle they spawn.
+    sub_emitters: Vec<(usize, SubEmitter)>,
+}
+
+#[derive(Debug)]
+pub struct Particle {
+    /// Index of the particle type, in name order.
+    pub kind: usize,
+    pub char: char,
+    pub color: Color,
+    pub physics: Projectile,
+    pub life: Lifetime,
+    /// Row at which a launched particle fires its apex sub-emitters even if
+    /// it is still climbing.
+    pub target: Option<f32>,
+}
+
+impl Body for Particle {
+    fn physics(&self) -> &Projectile {
+        &self.physics
+    }
+
+    fn physics_mut(&mut self) -> &mut Projectile {
+        &mut self.physics
+    }
+
+    fn color(&self) -> Color {
+        self.color
+    }
+}
+
+/// A [`Simulation`] driven by an [`EffectDefinition`].
+#[derive(Debug)]
+pub struct SimulationStateEffect {
+    pub name: String,
+    /// The particles and what moves and draws them. Its palette colours the
+    /// particle types that do not define their own, and its glyphs draw
+    /// those that use the glyph set.
+    pub system: ParticleSystem<Particle>,
+    kinds: Vec<Kind>,
+    /// Emitters with the type of particle they spawn.
+    emitters: Vec<(usize, Emitter)>,
+    /// How each of `emitters` spawns where the mouse is clicked or dragged,
+    /// with an emitter that paces the stream while dragged.
+    pointers: Vec<(PointerDefinition, Emitter)>,
+    /// Multiplier on the number of particles spawned.
+    pub intensity: f32,
+    gravity: Vector2<f32>,
+    /// Built-in simulation whose variant of the glyph set is used.
+    style: Mode,
+}
+
+impl SimulationStateEffect {
+    pub fn new(definition: EffectDefinition) -> Result<Self, EffectError> {
+        Self::with_seed(definition, rand::random())
+    }
+
+    /// Constructs a simulation whose particles are drawn from `seed`, so the
+    /// same input always produces the same frames.
+    pub fn with_seed(definition: EffectDefinition, seed: u64) -> Result<Self, EffectError> {
+        definition.validate()?;
+        let index = |name: &str| {
+            definition
+                .particles
+                .keys()
+                .position(|key| key == name)
+                .expect("validated")
+        };
+
+        let kinds = definition
+            .particles
+            .values()
+            .map(|particle| Kind {
+                chars: particle.chars.chars().collect(),
+                glyphs: particle.glyphs,
+                ramp: particle.ramp.iter().flat_map(|ramp| ramp.chars()).collect(),
+                palette: particle
+                    .palette
+                    .as_ref()
+                    .map(|palette| palette.iter().filter_map(|c| parse_hex(c)).collect()),
+                gradient: particle
+                    .gradient
+                    .iter()
+                    .flatten()
+                    .filter_map(|c| parse_hex(c))
+                    .collect(),
+                inherit_color: particle.inherit_color,
+                lifetime: particle.lifetime,
+                tail: particle.tail,
+                sub_emitters: particle
+                    .sub_emitters
+                    .iter()
+                    .map(|sub_emitter| {
+                        let spawner = SubEmitter::new(
+                            sub_emitter.trigger,
+                            sub_emitter.count,
+                            sub_emitter.velocity,
+                        );
+                        (index(&sub_emitter.particle), spawner)
+                    })
+                    .collect(),
+            })
+            .collect();
+        let emitters: Vec<_> = definition
+            .emitters
+            .iter()
+            .map(|emitter| (index(&emitter.particle), emitter.emitter()))
+            .collect();
+        let pointers = definition
+            .emitters
+            .iter()
+            .map(|emitter| {
+                let (velocity, rate) = match emitter.pointer {
+                    PointerDefinition::At { velocity, rate } => (
+                        velocity.unwrap_or(emitter.velocity),
+                        rate.unwrap_or(2.0 * emitter.emitter().burst_size() as f32),
+                    ),
+                    PointerDefinition::Aim { rate } | PointerDefinition::Launch { rate } => {
+                        (emitter.velocity, rate)
+                    }
+                };
+                let pacer =
+                    Emitter::new(Shape::default(), velocity).with_rate(Rate::Continuous(rate));
+                (emitter.pointer, pacer)
+            })
+            .collect();
+
+        let mut forces = ForceField::new();
+        for force in &definition.forces {
+            forces.push((*force).into());
+        }
+        let glyphs = GlyphSet::default().for_mode(definition.style);
+
+        Ok(Self {
+            name: definition.name,
+            system: ParticleSystem::with_seed(seed, forces, glyphs),
+            kinds,
+            emitters,
+            pointers,
+            intensity: 1.0,
+            gravity: Vector2::new(definition.gravity[0], definition.gravity[1]),
+            style: definition.style,
+        })
+    }
+
+    fn spawn(&mut self, kind: usize, pos: Point2<f32>, vel: Vector2<f32>, parent: Option<Color>) {
+        let def = &self.kinds[kind];
+        let system = &mut self.system;
+        let char = match def.glyphs {
+            Some(GlyphRole::Pieces) => system.glyphs.pieces.choose(&mut system.rng).copied(),
+            Some(GlyphRole::Head) => Some(system.glyphs.head),
+            Some(GlyphRole::Sparks) => system.glyphs.sparks.first().copied(),
+            None => def.chars.choose(&mut system.rng).copied(),
+        }
+        .unwrap_or('*');
+        let color = match (def.inherit_color, parent) {
+            (true, Some(color)) => color,
+            _ => match (def.gradient.first(), &def.palette) {
+                (Some(&color), _) => color,
+                (None, Some(palette)) => *palette.choose(&mut system.rng).unwrap_or(&Color::Reset),
+                (None, None) => system.palette.choose(&mut system.rng),
+            },
+        };
+        let life = match def.lifetime {
+            Some(lifetime) => {
+                let lifespan = sample(&mut system.rng, lifetime).max(0.0);
+                Lifetime::new(system.clock, Duration::from_secs_f32(lifespan))
+            }
+            None => Lifetime::immortal(system.clock),
+        };
+        let physics = system.projectile(pos, vel, self.gravity);
+        system.particles.push(Particle {
+            kind,
+            char,
+            color,
+            physics,
+            life,
+            target: None,
+        });
+    }
+
+    /// Culls particles that have left `area` or burnt out, and fires the
+    /// sub-emitters of those that triggered one.
+    pub fn update(&mut self, area: Rect) {
+        let mut indices_to_remove = vec![];
+        let mut emissions = vec![];
+        for (index, particle) in self.system.particles.iter().enumerate() {
+            let sub_emitters = &self.kinds[particle.kind].sub_emitters;
+            let triggers = sub_emitters
+                .iter()
+                .map(|(_, sub_emitter)| &sub_emitter.trigger);
+            let mut outcome = events::resolve(
+                triggers.clone(),
+                &particle.physics,
+                &particle.life,
+                self.system.clock,
+                area,
+            );
+            // Launched particles burst where they were aimed, as if they had
+            // reached their apex.
+            if particle
+                .target
+                .is_some_and(|y| particle.physics.position().y <= y)
+            {
+                outcome.removed = true;
+                outcome.fired = triggers
+                    .enumerate()
+                    .filter(|(_, trigger)| matches!(trigger, Trigger::Apex { .. } | Trigger::Death))
+                    .map(|(index, _)| index)
+                    .collect();
+            }
+            if outcome.removed {
+                indices_to_remove.push(index);
+            }
+            for fired in outcome.fired {
+                emissions.push((sub_emitters[fired], outcome.origin, particle.color));
+            }
+        }
+
+        self.system.remove_indices_from_particles(indices_to_remove);
+        for ((kind, mut sub_emitter), origin, color) in emissions {
+            sub_emitter.count = scaled(sub_emitter.count, self.intensity);
+            let velocities: Vec<_> = sub_emitter.emit(&mut self.system.rng, &origin).collect();
+            for vel in velocities {
+                self.spawn(kind, origin.pos, vel, Some(color));
+            }
+        }
+    }
+
+    // Spawns `count` particles from emitter `index`.
+    fn emit(&mut self, index: usize, count: usize, area: Rect) {
+        for _ in 0..count {
+            let (kind, emitter) = &self.emitters[index];
+            let kind = *kind;
+            let (pos, vel) = emitter.emit(&mut self.system.rng, area);
+            self.spawn(kind, pos, vel, None);
+        }
+    }
+
+    // Spawns `count` particles of emitter `index` the way its pointer does
+    // for `target`.
+    fn emit_at(&mut self, index: usize, count: usize, area: Rect, target: Point2<f32>) {
+        let kind = self.emitters[index].0;
+        for _ in 0..count {
+            let (pos, vel) = match self.pointers[index].0 {
+                PointerDefinition::At { .. } => {
+                    let pointer = &mut self.pointers[index].1;
+                    pointer.shape = Shape::at_cell(target, area);
+                    pointer.emit(&mut self.system.rng, area)
+                }
+                PointerDefinition::Aim { .. } => {
+                    let (pos, vel) = self.emitters[index].1.emit(&mut self.system.rng, area);
+                    let aim = target - pos;
+                    match aim.norm() > f32::EPSILON {
+                        true => (pos, aim.normalize() * vel.norm()),
+                        false => (pos, vel),
+                    }
+                }
+                PointerDefinition::Launch { .. } => {
+                    let bottom = area.height.saturating_sub(1) as f32;
+                    let rise = (bottom - target.y).max(0.0);
+                    // Fast enough to climb `rise` cells against gravity, with
+                    // some to spare for drag; the particle bursts as soon as
+                    // it gets there.
+                    let speed = (2.0 * self.gravity.y.max(0.0) * rise).sqrt() * 1.25 + 5.0;
+                    (Point2::new(target.x, bottom), Vector2::new(0.0, -speed))
+                }
+            };
+            self.spawn(kind, pos, vel, None);
+            if let (PointerDefinition::Launch { .. }, Some(particle)) =
+                (self.pointers[index].0, self.system.particles.last_mut())
+            {
+                particle.target = Some(target.y);
+            }
+        }
+    }
+}
+
+impl Simulation for SimulationStateEffect {
+    fn tick(&mut self, area: Rect, dt: Duration) {
+        self.system.tick(area, dt);
+        for index in 0..self.emitters.len() {
+            let due = self.emitters[index].1.advance(dt);
+            self.emit(index, due, area);
+        }
+        self.update(area);
+    }
+
+    fn spawn_particles(&mut self, area: Rect) -> usize {
+        let before = self.system.particles.len();
+        for index in 0..self.emitters.len() {
+            let count = scaled(self.emitters[index].1.burst_size(), self.intensity);
+            self.emit(index, count, area);
+        }
+        self.system.particles.len() - before
+    }
+
+    fn spawn_at(&mut self, area: Rect, target: Point2<f32>) -> usize {
+        let before = self.system.particles.len();
+        for index in 0..self.emitters.len() {
+            let count = match self.pointers[index].0 {
+                PointerDefinition::At { .. } => {
+                    scaled(self.emitters[index].1.burst_size(), self.intensity)
+                }
+                PointerDefinition::Aim { .. } | PointerDefinition::Launch { .. } => 1,
+            };
+            self.emit_at(index, count, area, target);
+        }
+        self.system.particles.len() - before
+    }
+
+    fn stream_at(&mut self, area: Rect, target: Point2<f32>, dt: Duration) -> usize {
+        let before = self.system.particles.len();
+        let dt = dt.mul_f32(self.intensity.max(0.0));
+        for index in 0..self.pointers.len() {
+            let due = self.pointers[index].1.advance(dt);
+            self.emit_at(index, due, area, target);
+        }
+        self.system.particles.len() - before
+    }
+
+    fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity;
+    }
+
+    fn set_palette(&mut self, palette: Palette) {
+        self.system.palette = palette;
+    }
+
+    fn set_glyphs(&mut self, glyphs: &GlyphSet) {
+        self.system.set_glyphs(glyphs.for_mode(self.style));
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        let mut painter = self.system.painter(area, buf);
+        let background = self.system.render_options.backdrop;
+        for particle in &self.system.particles {
+            let kind = &self.kinds[particle.kind];
+            let pos = particle.physics.position();
+
+            // The afterglow replaces the tail drawn from the velocity.
+            if let (Some(tail), None) = (kind.tail, &self.system.trail) {
+                let vel = particle.physics.velocity();
+                // Anything longer than the area is clipped anyway.
+                let longest = usize::from(area.width) + usize::from(area.height);
+                let cells = ((vel.norm() * tail.length) as usize).min(longest);
+                let tail_char = tail.char.unwrap_or(self.system.glyphs.tail);
+                if cells > 0 {
+                    let step = vel.normalize();
+                    for i in 1..=cells {
+                        let tail_pos = pos - step * i as f32;
+                        painter.put(tail_pos.x, tail_pos.y, tail_char, particle.color);
+                    }
+                }
+            }
+
+            let ramp = match kind.glyphs {
+                Some(GlyphRole::Sparks) if kind.ramp.is_empty() => &self.system.glyphs.sparks,
+                _ => &kind.ramp,
+            };
+            let color = match kind.gradient.is_empty() {
+                true => particle.color,
+                false => color::gradient(&kind.gradient, particle.life.progress(self.system.clock)),
+            };
+            let (char, color) = lifetime::appearance(
+                &particle.life,
+                self.system.clock,
+                (particle.char, color),
+                ramp,
+                background,
+            );
+            painter.put(pos.x, pos.y, char, color);
+        }
+    }
+
+    fn resize(&mut self, from: Rect, to: Rect, remap: bool) {
+        if !remap {
+            return;
+        }
+        self.system.remap(from, to);
+        for particle in &mut self.system.particles {
+            particle.target = particle
+                .target
+                .map(|y| simulation::remap(Point2::new(0.0, y), from, to).y);
+        }
+    }
+
+    fn particle_count(&self) -> usize {
+        self.system.particles.len()
+    }
+
+    fn reset(&mut self) {
+        self.system.reset();
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.system.reseed(seed);
+    }
+
+    fn set_collisions(&mut self, collisions: Option<Collisions>) {
+        self.system.collisions = collisions;
+    }
+
+    fn set_integrator(&mut self, integrator: Integrator) {
+        self.system.set_integrator(integrator);
+    }
+
+    fn set_render_options(&mut self, options: RenderOptions) {
+        self.system.render_options = options;
+    }
+
+    fn set_trail(&mut self, decay: Option<f32>) {
+        self.system.set_trail(decay);
+    }
+
+    fn force_field_mut(&mut self) -> Option<&mut ForceField> {
+        Some(&mut self.system.forces)
+    }
+}
+
+simulation_widget!(SimulationStateEffect);
+
+/// Declares a built-in simulation: a [`SimulationStateEffect`] running the
+/// bundled definition of `$mode`, under its own type.
+macro_rules! builtin_simulation {
+    ($(#[$meta:meta])* $name:ident, $mode:expr) => {
+        $(#[$meta])*
+        #[derive(Debug)]
+        pub struct $name(pub $crate::effect::SimulationStateEffect);
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl $name {
+            pub fn new() -> Self {
+                Self::with_seed(rand::random())
+            }
+
+            /// Constructs a simulation whose particles are drawn from `seed`,
+            /// so the same input always produces the same frames.
+            pub fn with_seed(seed: u64) -> Self {
+                Self($mode.with_seed(seed))
+            }
+        }
+
+        impl std::ops::Deref for $name {
+            type Target = $crate::effect::SimulationStateEffect;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl std::ops::DerefMut for $name {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.0
+            }
+        }
+
+        impl $crate::simulation::Simulation for $name {
+            fn tick(&mut self, area: ratatui::layout::Rect, dt: std::time::Duration) {
+                self.0.tick(area, dt)
+            }
+
+            fn spawn_particles(&mut self, area: ratatui::layout::Rect) -> usize {
+                self.0.spawn_particles(area)
+            }
+
+            fn spawn_at(&mut self, area: ratatui::layout::Rect, target: nalgebra::Point2<f32>) -> usize {
+                self.0.spawn_at(area, target)
+            }
+
+            fn stream_at(
+                &mut self,
+                area: ratatui::layout::Rect,
+                target: nalgebra::Point2<f32>,
+                dt: std::time::Duration,
+            ) -> usize {
+                self.0.stream_at(area, target, dt)
+            }
+
+            fn set_intensity(&mut self, intensity: f32) {
+                self.0.set_intensity(intensity)
+            }
+
+            fn set_palette(&mut self, palette: $crate::palette::Palette) {
+                self.0.set_palette(palette)
+            }
+
+            fn set_glyphs(&mut self, glyphs: &$crate::glyphs::GlyphSet) {
+                self.0.set_glyphs(glyphs)
+            }
+
+            fn render(&self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+                $crate::simulation::Simulation::render(&self.0, area, buf)
+            }
+
+            fn resize(&mut self, from: ratatui::layout::Rect, to: ratatui::layout::Rect, remap: bool) {
+                self.0.resize(from, to, remap)
+            }
+
+            fn particle_count(&self) -> usize {
+                self.0.particle_count()
+            }
+
+            fn reset(&mut self) {
+                self.0.reset()
+            }
+
+            fn reseed(&mut self, seed: u64) {
+                self.0.reseed(seed)
+            }
+
+            fn set_collisions(&mut self, collisions: Option<$crate::collision::Collisions>) {
+                self.0.set_collisions(collisions)
+            }
+
+            fn set_integrator(&mut self, integrator: $crate::projectile::Integrator) {
+                self.0.set_integrator(integrator)
+            }
+
+            fn set_render_options(&mut self, options: $crate::render::RenderOptions) {
+                self.0.set_render_options(options)
+            }
+
+            fn set_trail(&mut self, decay: Option<f32>) {
+                self.0.set_trail(decay)
+            }
+
+            fn force_field_mut(&mut self) -> Option<&mut $crate::forces::ForceField> {
+                self.0.force_field_mut()
+            }
+        }
+
+        $crate::simulation::simulation_widget!($name);
+    };
+}
+
+pub(crate) use builtin_simulation;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_effects_parse() {
+        for (name, _) in BUILTIN_EFFECTS {
+            let definition = EffectDefinition::builtin(name).unwrap();
+            assert_eq!(definition.name, name);
+            assert!(SimulationStateEffect::with_seed(definition, 0).is_ok());
+        }
+        assert!(EffectDefinition::builtin("sparklers").is_none());
+    }
+
+    #[test]
+    fn sparks_fire_their_own_sub_emitters() {
+        let definition = EffectDefinition::builtin("crackle").unwrap();
+        let mut effect = SimulationStateEffect::with_seed(definition, 0).unwrap();
+        let area = Rect::new(0, 0, 80, 40);
+        let dt = Duration::from_millis(33);
+        // Particle types are indexed in name order.
+        let (crackle, spark) = (0, 2);
+        let count = |effect: &SimulationStateEffect, kind: usize| {
+            effect
+                .system
+                .particles
+                .iter()
+                .filter(|p| p.kind == kind)
+                .count()
+        };
+
+        effect.spawn_particles(area);
+        (0..200)
+            .find(|_| {
+                effect.tick(area, dt);
+                count(&effect, spark) > 0
+            })
+            .expect("rocket never exploded");
+        assert_eq!(count(&effect, spark), 24);
+
+        (0..200)
+            .find(|_| {
+                effect.tick(area, dt);
+                count(&effect, crackle) > 0
+            })
+            .expect("sparks never crackled");
+    }
+
+    #[test]
+    fn glyph_sets_draw_particles_that_use_them() {
+        let definition = EffectDefinition::builtin("stars").unwrap();
+        let mut effect = SimulationStateEffect::with_seed(definition, 0).unwrap();
+        effect.set_glyphs(&GlyphSet::Ascii);
+        effect.spawn_particles(Rect::new(0, 0, 80, 40));
+        assert!(effect
+            .system
+            .particles
+            .iter()
+            .all(|particle| particle.char == 'O'));
+        assert_eq!(effect.system.glyphs.tail, '.');
+    }
+
+    #[test]
+    fn rejects_unknown_particle_types_and_fields() {
+        let dangling = r#"
+            name = "dangling"
+            [[emitters]]
+            particle = "missing"
+            velocity = { type = "cartesian", x = [0.0, 0.0], y = [0.0, 0.0] }
+            [particles.spark]
+            chars = "*"
+        "#;
+        assert!(matches!(
+            EffectDefinition::from_toml(dangling),
+            Err(EffectError::Invalid(_))
+        ));
+
+        let typo = r#"
+            name = "typo"
+            emitters = []
+            [particles.spark]
+            char = "*"
+        "#;
+        assert!(matches!(
+            EffectDefinition::from_toml(typo),
+            Err(EffectError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_unbounded_lifetimes() {
+        let with_lifetime = |lifetime: &str| {
+            format!(
+                r#"
+                name = "lifetime"
+                emitters = []
+                [particles.spark]
+                chars = "*"
+                lifetime = {lifetime}
+            "#
+            )
+        };
+        assert!(EffectDefinition::from_toml(&with_lifetime("[0.5, 1.0]")).is_ok());
+        for lifetime in [
+            "[1.0, inf]",
+            "[nan, 1.0]",
+            "[-1.0, 1.0]",
+            "[2.0, 1.0]",
+            "[1.0, 1e30]",
+        ] {
+            assert!(
+                matches!(
+                    EffectDefinition::from_toml(&with_lifetime(lifetime)),
+                    Err(EffectError::Invalid(_))
+                ),
+                "accepted lifetime {lifetime}"
+            );
+        }
+    }
+
+    fn assert_invalid(source: &str) {
+        assert!(
+            matches!(
+                EffectDefinition::from_toml(source),
+                Err(EffectError::Invalid(_))
+            ),
+            "accepted {source}"
+        );
+    }
+
+    #[test]
+    fn rejects_unbounded_rates_and_counts() {
+        let with_emitter = |emitter: &str, count: usize| {
+            format!(
+                r#"
+                name = "rates"
+                [[emitters]]
+                particle = "spark"
+                velocity = {{ type = "cartesian", x = 0.0, y = 0.0 }}
+                {emitter}
+                [particles.spark]
+                chars = "*"
+                [[particles.spark.sub_emitters]]
+                trigger = {{ on = "expire" }}
+                particle = "ember"
+                count = {count}
+                velocity = {{ type = "cone", spread = 180.0, speed = 1.0 }}
+                [particles.ember]
+                chars = "."
+            "#
+            )
+        };
+        assert!(EffectDefinition::from_toml(&with_emitter("", 1)).is_ok());
+        for emitter in [
+            "rate = { continuous = 1e30 }",
+            "rate = { continuous = nan }",
+            "rate = { continuous = -1.0 }",
+            "rate = { burst = 1000000000 }",
+            "pointer = { type = \"aim\", rate = inf }",
+            "pointer = { type = \"at\", rate = 1e30 }",
+        ] {
+            assert_invalid(&with_emitter(emitter, 1));
+        }
+        assert_invalid(&with_emitter("", 1_000_000_000));
+    }
+
+    #[test]
+    fn rejects_numbers_that_are_not_finite() {
+        let effect = |gravity: &str, force: &str, velocity: &str, sub_velocity: &str| {
+            format!(
+                r#"
+                name = "finite"
+                gravity = {gravity}
+                forces = [{force}]
+                [[emitters]]
+                particle = "rocket"
+                velocity = {velocity}
+                [particles.rocket]
+                chars = "^"
+                [[particles.rocket.sub_emitters]]
+                trigger = {{ on = "apex", threshold = 3.0 }}
+                particle = "spark"
+                velocity = {sub_velocity}
+                [particles.spark]
+                chars = "*"
+            "#
+            )
+        };
+        let (gravity, force) = ("[0.0, 9.81]", r#"{ type = "quadratic-drag", k = 0.01 }"#);
+        let velocity = r#"{ type = "cartesian", x = 0.0, y = [-30.0, -15.0] }"#;
+        let sub_velocity = r#"{ type = "cone", spread = 180.0, speed = [20.0, 30.0] }"#;
+        assert!(
+            EffectDefinition::from_toml(&effect(gravity, force, velocity, sub_velocity)).is_ok()
+        );
+
+        assert_invalid(&effect("[0.0, nan]", force, velocity, sub_velocity));
+        assert_invalid(&effect(
+            gravity,
+            r#"{ type = "linear-drag", k = inf }"#,
+            velocity,
+            sub_velocity,
+        ));
+        assert_invalid(&effect(
+            gravity,
+            force,
+            r#"{ type = "cartesian", x = 0.0, y = [-inf, -15.0] }"#,
+            sub_velocity,
+        ));
+        assert_invalid(&effect(
+            gravity,
+            force,
+            velocity,
+            r#"{ type = "cone", spread = nan, speed = [20.0, 30.0] }"#,
+        ));
+    }
+
+    #[test]
+    fn rejects_sub_emitter_cycles() {
+        let effect = |spark_spawns: &str| {
+            format!(
+                r#"
+                name = "cycle"
+                [[emitters]]
+                particle = "spark"
+                velocity = {{ type = "cartesian", x = 0.0, y = 0.0 }}
+                [particles.spark]
+                chars = "*"
+                lifetime = [0.5, 1.0]
+                [[particles.spark.sub_emitters]]
+                trigger = {{ on = "death" }}
+                particle = "{spark_spawns}"
+                velocity = {{ type = "cone", spread = 180.0, speed = 1.0 }}
+                [particles.ember]
+                chars = "."
+                [[particles.ember.sub_emitters]]
+                trigger = {{ on = "expire" }}
+                particle = "ash"
+                velocity = {{ type = "cone", spread = 180.0, speed = 1.0 }}
+                [particles.ash]
+                chars = ","
+            "#
+            )
+        };
+        assert!(EffectDefinition::from_toml(&effect("ember")).is_ok());
+        assert_invalid(&effect("spark"));
+        // A longer loop, through another type.
+        let through_ember = effect("ember").replace(r#"particle = "ash""#, r#"particle = "spark""#);
+        assert_invalid(&through_ember);
+    }
+
+    #[test]
+    fn gradients_colour_particles_over_their_life() {
+        let source = r##"
+            name = "gradient"
+            gravity = [0.0, 0.0]
+            [[emitters]]
+            particle = "ember"
+            velocity = { type = "cartesian", x = 0.0, y = 0.0 }
+            [particles.ember]
+            chars = "*"
+            gradient = ["#ff0000", "#0000ff"]
+            lifetime = [2.0, 2.0]
+        "##;
+        let definition = EffectDefinition::from_toml(source).unwrap();
+        let mut effect = SimulationStateEffect::with_seed(definition, 0).unwrap();
+        let area = Rect::new(0, 0, 9, 9);
+        let (red, blue) = (Color::Rgb(255, 0, 0), Color::Rgb(0, 0, 255));
+        let color_at_centre = |effect: &SimulationStateEffect| {
+            let mut buf = Buffer::empty(area);
+            Simulation::render(effect, area, &mut buf);
+            buf.get(4, 4).fg
+        };
+
+        effect.spawn_particles(area);
+        assert_eq!(color_at_centre(&effect), red);
+        effect.tick(area, Duration::from_millis(500));
+        assert_eq!(
+            color_at_centre(&effect),
+            color::gradient(&[red, blue], 0.25)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_palette_colours() {
+        let source = r##"
+            name = "palette"
+            emitters = []
+            [particles.spark]
+            chars = "*"
+            palette = ["#ff0000", "red"]
+        "##;
+        assert!(matches!(
+            EffectDefinition::from_toml(source),
+            Err(EffectError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,457 @@
+use nalgebra::{Point2, Vector2};
+use rand::Rng;
+use ratatui::layout::Rect;
+use serde::Deserialize;
+use std::f32::consts::PI;
+use std::time::Duration;
+
+/// Where an [`Emitter`] spawns particles. Coordinates are fractions of the
+/// area, so `[0.5, 0.5]` is its centre and `[0.0, 1.0]` its bottom-left
+/// cell, and shapes follow the area when it is resized.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum Shape {
+    Point {
+        at: [f32; 2],
+    },
+    /// A uniformly sampled point on the segment.
+    Line {
+        from: [f32; 2],
+        to: [f32; 2],
+    },
+    /// A uniformly sampled point inside the rectangle.
+    Rect {
+        min: [f32; 2],
+        max: [f32; 2],
+    },
+    /// A uniformly sampled point inside the circle. `radius` is a fraction of
+    /// the shorter side of the area.
+    Circle {
+        center: [f32; 2],
+        radius: f32,
+    },
+    /// A uniformly sampled point on the edge of the ellipse.
+    Ellipse {
+        center: [f32; 2],
+        radii: [f32; 2],
+    },
+}
+
+impl Default for Shape {
+    fn default() -> Self {
+        Shape::Point { at: [0.5, 0.5] }
+    }
+}
+
+impl Shape {
+    /// Returns whether every coordinate of the shape is a finite number.
+    pub fn is_finite(&self) -> bool {
+        let values = match *self {
+            Shape::Point { at } => vec![at[0], at[1]],
+            Shape::Line { from, to } => vec![from[0], from[1], to[0], to[1]],
+            Shape::Rect { min, max } => vec![min[0], min[1], max[0], max[1]],
+            Shape::Circle { center, radius } => vec![center[0], center[1], radius],
+            Shape::Ellipse { center, radii } => vec![center[0], center[1], radii[0], radii[1]],
+        };
+        values.iter().all(|v| v.is_finite())
+    }
+
+    /// A point at `pos`, a position inside `area` relative to its top-left
+    /// corner.
+    pub fn at_cell(pos: Point2<f32>, area: Rect) -> Self {
+        let width = f32::from(area.width.max(1));
+        let height = f32::from(area.height.max(1));
+        Shape::Point {
+            at: [pos.x / width, pos.y / height],
+        }
+    }
+
+    /// Returns a spawn position inside `area`, relative to its top-left
+    /// corner, and the centre of the shape.
+    pub fn sample(&self, rng: &mut impl Rng, area: Rect) -> (Point2<f32>, Point2<f32>) {
+        let (width, height) = (area.width as f32, area.height as f32);
+        let scale = |[x, y]: [f32; 2]| Point2::new(x * width, y * height);
+        let (pos, center) = match *self {
+            Shape::Point { at } => (scale(at), scale(at)),
+            Shape::Line { from, to } => {
+                let (from, to) = (scale(from), scale(to));
+                (
+                    from + (to - from) * rng.gen::<f32>(),
+                    nalgebra::center(&from, &to),
+                )
+            }
+            Shape::Rect { min, max } => {
+                let (min, max) = (scale(min), scale(max));
+                let pos = Point2::new(
+                    min.x + (max.x - min.x) * rng.gen::<f32>(),
+                    min.y + (max.y - min.y) * rng.gen::<f32>(),
+                );
+                (pos, nalgebra::center(&min, &max))
+            }
+            Shape::Circle { center, radius } => {
+                let center = scale(center);
+                let angle = rng.gen::<f32>() * 2.0 * PI;
+                // The square root spreads points evenly over the disc instead
+                // of bunching them at the centre.
+                let r = radius * width.min(height) * rng.gen::<f32>().sqrt();
+                (center + Vector2::new(angle.cos(), angle.sin()) * r, center)
+            }
+            Shape::Ellipse { center, radii } => {
+                let center = scale(center);
+                let angle = rng.gen::<f32>() * 2.0 * PI;
+                let offset = Vector2::new(
+                    radii[0] * width * angle.cos(),
+                    radii[1] * height * angle.sin(),
+                );
+                (center + offset, center)
+            }
+        };
+        // Keep positions on a cell, so points on the far edges stay visible.
+        let clamp = |v: f32, len: u16| v.clamp(0.0, len.saturating_sub(1) as f32);
+        (
+            Point2::new(clamp(pos.x, area.width), clamp(pos.y, area.height)),
+            center,
+        )
+    }
+}
+
+/// How a speed, or any other scalar, is drawn for each particle.
+///
+/// Written in a definition as a number, a `[min, max]` pair, or a table with
+/// `mean` and `std-dev`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum Speed {
+    Constant(f32),
+    Uniform([f32; 2]),
+    #[serde(rename_all = "kebab-case")]
+    Normal {
+        mean: f32,
+        std_dev: f32,
+    },
+}
+
+impl Speed {
+    /// Returns whether every parameter of the distribution is finite.
+    pub fn is_finite(&self) -> bool {
+        match *self {
+            Speed::Constant(speed) => speed.is_finite(),
+            Speed::Uniform([min, max]) => min.is_finite() && max.is_finite(),
+            Speed::Normal { mean, std_dev } => mean.is_finite() && std_dev.is_finite(),
+        }
+    }
+
+    pub fn sample(&self, rng: &mut impl Rng) -> f32 {
+        match *self {
+            Speed::Constant(speed) => speed,
+            Speed::Uniform([min, max]) => min + (max - min) * rng.gen::<f32>(),
+            Speed::Normal { mean, std_dev } => {
+                // Box-Muller transform.
+                let u1 = rng.gen::<f32>().max(f32::MIN_POSITIVE);
+                let u2 = rng.gen::<f32>();
+                mean + std_dev * (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+            }
+        }
+    }
+}
+
+/// Initial velocity of a spawned particle, in cells per second.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum Velocity {
+    /// Independent speeds along each axis.
+    Cartesian { x: Speed, y: Speed },
+    /// Within `spread` degrees either side of `direction`, in degrees
+    /// clockwise from the +x axis since y grows downward. The result is
+    /// multiplied per axis by `scale`.
+    Cone {
+        #[serde(default)]
+        direction: f32,
+        #[serde(default)]
+        spread: f32,
+        speed: Speed,
+        #[serde(default = "unit_scale")]
+        scale: [f32; 2],
+    },
+    /// Toward the centre of the emitter shape.
+    Inward {
+        speed: Speed,
+        #[serde(default = "unit_scale")]
+        scale: [f32; 2],
+    },
+    /// The velocity of the emitting particle, mirrored off the wall it hit if
+    /// any, spread by up to `spread` degrees either way and scaled by a
+    /// factor drawn from `speed`.
+    Reflect {
+        speed: Speed,
+        #[serde(default)]
+        spread: f32,
+    },
+}
+
+// Returns an angle in radians of up to `degrees` either way.
+fn jitter(rng: &mut impl Rng, degrees: f32) -> f32 {
+    degrees.to_radians() * (rng.gen::<f32>() * 2.0 - 1.0)
+}
+
+fn unit_scale() -> [f32; 2] {
+    [1.0, 1.0]
+}
+
+/// Where and from what a particle is spawned.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Origin {
+    pub pos: Point2<f32>,
+    /// Centre of the shape the particle was spawned on.
+    pub center: Point2<f32>,
+    /// Velocity of the particle that emitted this one, if any.
+    pub velocity: Vector2<f32>,
+    /// Normal of the wall the emitting particle hit, pointing into the area.
+    pub normal: Option<Vector2<f32>>,
+}
+
+impl Origin {
+    /// An origin at `pos` with no emitting particle.
+    pub fn at(pos: Point2<f32>) -> Self {
+        Self {
+            pos,
+            center: pos,
+            velocity: Vector2::zeros(),
+            normal: None,
+        }
+    }
+}
+
+impl Velocity {
+    /// Returns whether every parameter of the velocity is finite.
+    pub fn is_finite(&self) -> bool {
+        match *self {
+            Velocity::Cartesian { x, y } => x.is_finite() && y.is_finite(),
+            Velocity::Cone {
+                direction,
+                spread,
+                speed,
+                scale,
+            } => {
+                direction.is_finite()
+                    && spread.is_finite()
+                    && speed.is_finite()
+                    && scale.iter().all(|v| v.is_finite())
+            }
+            Velocity::Inward { speed, scale } => {
+                speed.is_finite() && scale.iter().all(|v| v.is_finite())
+            }
+            Velocity::Reflect { speed, spread } => speed.is_finite() && spread.is_finite(),
+        }
+    }
+
+    pub fn sample(&self, rng: &mut impl Rng, origin: &Origin) -> Vector2<f32> {
+        match *self {
+            Velocity::Cartesian { x, y } => Vector2::new(x.sample(rng), y.sample(rng)),
+            Velocity::Cone {
+                direction,
+                spread: degrees,
+                speed,
+                scale,
+            } => {
+                let angle = direction.to_radians() + jitter(rng, degrees);
+                let speed = speed.sample(rng);
+                Vector2::new(
+                    angle.cos() * speed * scale[0],
+                    angle.sin() * speed * scale[1],
+                )
+            }
+            Velocity::Inward { speed, scale } => {
+                let offset = origin.center - origin.pos;
+                let direction = if offset.norm() > f32::EPSILON {
+                    offset.normalize()
+                } else {
+                    let angle = rng.gen::<f32>() * 2.0 * PI;
+                    Vector2::new(angle.cos(), angle.sin())
+                };
+                direction.component_mul(&Vector2::new(scale[0], scale[1])) * speed.sample(rng)
+            }
+            Velocity::Reflect {
+                speed,
+                spread: degrees,
+            } => {
+                let v = origin.velocity;
+                let reflected = match origin.normal {
+                    Some(n) if v.dot(&n) < 0.0 => v - n * (2.0 * v.dot(&n)),
+                    _ => v,
+                };
+                let angle = reflected.y.atan2(reflected.x) + jitter(rng, degrees);
+                Vector2::new(angle.cos(), angle.sin()) * v.norm() * speed.sample(rng)
+            }
+        }
+    }
+}
+
+/// How many particles an [`Emitter`] releases.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub enum Rate {
+    /// This many at once, every time particles are spawned.
+    Burst(usize),
+    /// A steady stream of this many per second while the simulation runs.
+    Continuous(f32),
+}
+
+impl Default for Rate {
+    fn default() -> Self {
+        Rate::Burst(1)
+    }
+}
+
+/// Scales a particle count by `intensity`, keeping at least one particle
+/// unless there were none to begin with.
+pub fn scaled(count: usize, intensity: f32) -> usize {
+    if count == 0 {
+        return 0;
+    }
+    ((count as f32 * intensity.max(0.0)).round() as usize).max(1)
+}
+
+/// Spawns particles on a [`Shape`] with a [`Velocity`], either in bursts or
+/// as a steady stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Emitter {
+    pub shape: Shape,
+    pub velocity: Velocity,
+    pub rate: Rate,
+    /// Fraction of a particle a continuous emitter still owes.
+    pending: f32,
+}
+
+impl Emitter {
+    /// Constructs an [`Emitter`] that releases one particle per burst.
+    pub fn new(shape: Shape, velocity: Velocity) -> Self {
+        Self {
+            shape,
+            velocity,
+            rate: Rate::default(),
+            pending: 0.0,
+        }
+    }
+
+    /// Returns the emitter with `rate`.
+    pub fn with_rate(mut self, rate: Rate) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    /// Returns how many particles a burst releases. Continuous emitters
+    /// release none.
+    pub fn burst_size(&self) -> usize {
+        match self.rate {
+            Rate::Burst(count) => count,
+            Rate::Continuous(_) => 0,
+        }
+    }
+
+    /// Returns how many particles a continuous emitter releases over `dt`,
+    /// carrying fractions over to the next call. Burst emitters release none.
+    pub fn advance(&mut self, dt: Duration) -> usize {
+        let Rate::Continuous(per_second) = self.rate else {
+            return 0;
+        };
+        self.pending += per_second.max(0.0) * dt.as_secs_f32();
+        let due = self.pending.floor();
+        self.pending -= due;
+        due as usize
+    }
+
+    /// Returns the position and velocity of a new particle inside `area`,
+    /// relative to its top-left corner.
+    pub fn emit(&self, rng: &mut impl Rng, area: Rect) -> (Point2<f32>, Vector2<f32>) {
+        let (pos, center) = self.shape.sample(rng, area);
+        let origin = Origin {
+            center,
+            ..Origin::at(pos)
+        };
+        (pos, self.velocity.sample(rng, &origin))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn shapes_spawn_inside_the_area() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let area = Rect::new(0, 0, 40, 20);
+        let shapes = [
+            Shape::Point { at: [1.0, 1.0] },
+            Shape::Line {
+                from: [0.0, 1.0],
+                to: [1.0, 1.0],
+            },
+            Shape::Rect {
+                min: [-0.5, -0.5],
+                max: [1.5, 1.5],
+            },
+            Shape::Circle {
+                center: [0.5, 0.5],
+                radius: 2.0,
+            },
+            Shape::Ellipse {
+                center: [0.5, 0.5],
+                radii: [0.4, 0.4],
+            },
+        ];
+        for shape in shapes {
+            for _ in 0..100 {
+                let (pos, _) = shape.sample(&mut rng, area);
+                assert!((0.0..=39.0).contains(&pos.x) && (0.0..=19.0).contains(&pos.y));
+            }
+        }
+    }
+
+    #[test]
+    fn cone_stays_within_its_spread() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let velocity = Velocity::Cone {
+            direction: -90.0,
+            spread: 30.0,
+            speed: Speed::Uniform([10.0, 20.0]),
+            scale: [1.0, 1.0],
+        };
+        for _ in 0..100 {
+            let v = velocity.sample(&mut rng, &Origin::at(Point2::origin()));
+            let angle = v.y.atan2(v.x).to_degrees();
+            assert!((-120.0..=-60.0).contains(&angle), "angle = {angle}");
+            assert!((10.0..=20.0).contains(&v.norm()));
+        }
+    }
+
+    #[test]
+    fn continuous_rate_carries_fractions_between_ticks() {
+        let mut emitter = Emitter::new(
+            Shape::default(),
+            Velocity::Cartesian {
+                x: Speed::Constant(0.0),
+                y: Speed::Constant(0.0),
+            },
+        )
+        .with_rate(Rate::Continuous(10.0));
+        let released: usize = (0..30)
+            .map(|_| emitter.advance(Duration::from_millis(33)))
+            .sum();
+        assert_eq!(released, 9);
+        assert_eq!(emitter.burst_size(), 0);
+    }
+
+    #[test]
+    fn normal_speed_clusters_around_its_mean() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let speed = Speed::Normal {
+            mean: 25.0,
+            std_dev: 2.0,
+        };
+        let mean = (0..1000).map(|_| speed.sample(&mut rng)).sum::<f32>() / 1000.0;
+        assert!((mean - 25.0).abs() < 0.5, "mean = {mean}");
+    }
+}
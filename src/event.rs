@@ -7,8 +7,8 @@ use std::time::{Duration, Instant};
 /// Terminal events.
 #[derive(Clone, Copy, Debug)]
 pub enum Event {
-    /// Terminal tick.
-    Tick,
+    /// Terminal tick, carrying the time elapsed since the previous one.
+    Tick(Duration),
     /// Key press.
     Key(KeyEvent),
     /// Mouse click/scroll.
@@ -55,8 +55,11 @@ impl EventHandler {
                         .expect("failed to send terminal event")
                     }
 
-                    if last_tick.elapsed() >= tick_rate {
-                        sender.send(Event::Tick).expect("failed to send tick event");
+                    let elapsed = last_tick.elapsed();
+                    if elapsed >= tick_rate {
+                        sender
+                            .send(Event::Tick(elapsed))
+                            .expect("failed to send tick event");
                         last_tick = Instant::now();
                     }
                 }
@@ -0,0 +1,234 @@
+use crate::emitter::{Origin, Velocity};
+use crate::lifetime::Lifetime;
+use crate::projectile::Projectile;
+use nalgebra::{Point2, Vector2};
+use rand::Rng;
+use ratatui::layout::Rect;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// What makes a particle fire a [`SubEmitter`].
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "on", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum Trigger {
+    /// Its upward speed drops below `threshold` cells per second.
+    Apex {
+        #[serde(default)]
+        threshold: f32,
+    },
+    /// It is removed for any reason, including firing another trigger.
+    Death,
+    /// It outlives its lifetime.
+    Expire,
+    /// It reaches any edge of the area.
+    Boundary,
+}
+
+/// Spawns `count` particles where a particle fires `trigger`. The particle
+/// that fires it is removed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SubEmitter {
+    pub trigger: Trigger,
+    pub count: usize,
+    pub velocity: Velocity,
+}
+
+impl SubEmitter {
+    pub fn new(trigger: Trigger, count: usize, velocity: Velocity) -> Self {
+        Self {
+            trigger,
+            count,
+            velocity,
+        }
+    }
+
+    /// Returns the velocities of the particles spawned from `origin`.
+    pub fn emit<'a>(
+        &'a self,
+        rng: &'a mut impl Rng,
+        origin: &'a Origin,
+    ) -> impl Iterator<Item = Vector2<f32>> + 'a {
+        (0..self.count).map(move |_| self.velocity.sample(rng, origin))
+    }
+}
+
+/// What happened to a particle during a tick.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome {
+    /// Whether the particle must be removed.
+    pub removed: bool,
+    /// Where sub-emitters spawn from: the particle itself, moved back onto
+    /// the wall it crossed, if any.
+    pub origin: Origin,
+    /// Indices of the triggers that fired.
+    pub fired: Vec<usize>,
+}
+
+/// Works out which of `triggers` a particle fires at simulation time `now`,
+/// and whether it dies.
+///
+/// Particles are culled once they leave `area` through its left, right or
+/// bottom edge. The top edge only counts when one of `triggers` reacts to
+/// boundaries, so ballistic particles may fly above the screen and fall
+/// back in.
+pub fn resolve<'a>(
+    triggers: impl IntoIterator<Item = &'a Trigger> + Clone,
+    physics: &Projectile,
+    life: &Lifetime,
+    now: Duration,
+    area: Rect,
+) -> Outcome {
+    let (width, height) = (area.width as f32, area.height as f32);
+    let pos = physics.position();
+    let vel = physics.velocity();
+
+    let top_is_wall = triggers
+        .clone()
+        .into_iter()
+        .any(|trigger| *trigger == Trigger::Boundary);
+    let normal = if pos.x < 0.0 {
+        Some(Vector2::new(1.0, 0.0))
+    } else if pos.x >= width {
+        Some(Vector2::new(-1.0, 0.0))
+    } else if pos.y >= height {
+        Some(Vector2::new(0.0, -1.0))
+    } else if pos.y < 0.0 && top_is_wall {
+        Some(Vector2::new(0.0, 1.0))
+    } else {
+        None
+    };
+    let expired = life.is_expired(now);
+
+    let fires = |trigger: &Trigger| match *trigger {
+        Trigger::Apex { threshold } => -vel.y < threshold,
+        Trigger::Death => false,
+        Trigger::Expire => expired,
+        Trigger::Boundary => normal.is_some(),
+    };
+    let mut fired: Vec<usize> = triggers
+        .clone()
+        .into_iter()
+        .enumerate()
+        .filter(|(_, trigger)| fires(trigger))
+        .map(|(index, _)| index)
+        .collect();
+
+    let removed = normal.is_some() || expired || !fired.is_empty();
+    if removed {
+        fired.extend(
+            triggers
+                .into_iter()
+                .enumerate()
+                .filter(|(_, trigger)| **trigger == Trigger::Death)
+                .map(|(index, _)| index),
+        );
+        fired.sort_unstable();
+    }
+
+    // Onto the middle of the last cell rather than the far edge itself,
+    // which is already outside the area.
+    let inside = |v: f32, len: f32| v.clamp(0.0, (len - 1.0).max(0.0) + 0.5);
+    let origin = Origin {
+        pos: match normal {
+            Some(_) => Point2::new(inside(pos.x, width), inside(pos.y, height)),
+            None => pos,
+        },
+        velocity: vel,
+        normal,
+        ..Origin::at(pos)
+    };
+    Outcome {
+        removed,
+        origin,
+        fired,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consts::TERMINAL_GRAVITY;
+
+    const AREA: Rect = Rect {
+        x: 0,
+        y: 0,
+        width: 40,
+        height: 20,
+    };
+
+    fn particle(pos: (f32, f32), vel: (f32, f32)) -> Projectile {
+        Projectile::new(
+            Point2::new(pos.0, pos.1),
+            Vector2::new(vel.0, vel.1),
+            TERMINAL_GRAVITY,
+        )
+    }
+
+    #[test]
+    fn apex_fires_once_the_particle_slows_down() {
+        let triggers = [Trigger::Apex { threshold: 3.0 }, Trigger::Death];
+        let life = Lifetime::immortal(Duration::ZERO);
+
+        let rising = resolve(
+            &triggers,
+            &particle((5.0, 5.0), (0.0, -10.0)),
+            &life,
+            Duration::ZERO,
+            AREA,
+        );
+        assert!(!rising.removed);
+        assert!(rising.fired.is_empty());
+
+        let apex = resolve(
+            &triggers,
+            &particle((5.0, 5.0), (0.0, -2.0)),
+            &life,
+            Duration::ZERO,
+            AREA,
+        );
+        assert!(apex.removed);
+        assert_eq!(apex.fired, vec![0, 1]);
+    }
+
+    #[test]
+    fn top_edge_is_only_a_wall_for_boundary_triggers() {
+        let life = Lifetime::immortal(Duration::ZERO);
+        let above = particle((5.0, -1.0), (0.0, -10.0));
+
+        let ballistic = resolve(&[], &above, &life, Duration::ZERO, AREA);
+        assert!(!ballistic.removed);
+
+        let bouncing = resolve(&[Trigger::Boundary], &above, &life, Duration::ZERO, AREA);
+        assert!(bouncing.removed);
+        assert_eq!(bouncing.fired, vec![0]);
+        assert_eq!(bouncing.origin.pos, Point2::new(5.0, 0.0));
+        assert_eq!(bouncing.origin.normal, Some(Vector2::new(0.0, 1.0)));
+    }
+
+    #[test]
+    fn far_walls_spawn_inside_the_area() {
+        let life = Lifetime::immortal(Duration::ZERO);
+        let corner = particle((45.0, 23.0), (10.0, 10.0));
+
+        let outcome = resolve(&[Trigger::Boundary], &corner, &life, Duration::ZERO, AREA);
+        assert!(outcome.removed);
+        let pos = outcome.origin.pos;
+        assert!(pos.x < f32::from(AREA.width), "spawned at x = {}", pos.x);
+        assert!(pos.y < f32::from(AREA.height), "spawned at y = {}", pos.y);
+        assert_eq!(pos, Point2::new(39.5, 19.5));
+    }
+
+    #[test]
+    fn expiry_fires_expire_and_death() {
+        let triggers = [Trigger::Expire, Trigger::Boundary, Trigger::Death];
+        let life = Lifetime::new(Duration::ZERO, Duration::from_secs(1));
+        let spark = particle((5.0, 5.0), (1.0, 1.0));
+
+        let alive = resolve(&triggers, &spark, &life, Duration::from_millis(500), AREA);
+        assert!(!alive.removed);
+
+        let dead = resolve(&triggers, &spark, &life, Duration::from_secs(1), AREA);
+        assert!(dead.removed);
+        assert_eq!(dead.fired, vec![0, 2]);
+    }
+}
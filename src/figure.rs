@@ -0,0 +1,163 @@
+use nalgebra::Point2;
+use ratatui::prelude::*;
+use std::collections::BTreeMap;
+
+/// One filled cell of a [`Figure`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FigurePoint {
+    pub x: usize,
+    pub y: usize,
+    /// Character the particle shows once in place, if not its own.
+    pub char: Option<char>,
+    /// Colour the particle takes, if not one from the palette.
+    pub color: Option<Color>,
+}
+
+/// A shape for particles to assemble into, as the filled cells of a
+/// `width` by `height` grid.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Figure {
+    pub width: usize,
+    pub height: usize,
+    pub points: Vec<FigurePoint>,
+}
+
+impl Figure {
+    /// Builds a figure from rows of text, filling every cell for which
+    /// `filled` holds. With `keep_chars` the particles show the characters of
+    /// the text once in place.
+    pub fn from_rows<S: AsRef<str>>(
+        rows: &[S],
+        filled: impl Fn(char) -> bool,
+        keep_chars: bool,
+    ) -> Self {
+        let mut figure = Self {
+            width: 0,
+            height: rows.len(),
+            points: vec![],
+        };
+        for (y, row) in rows.iter().enumerate() {
+            let row = row.as_ref();
+            figure.width = figure.width.max(row.chars().count());
+            for (x, char) in row.chars().enumerate().filter(|(_, char)| filled(*char)) {
+                figure.points.push(FigurePoint {
+                    x,
+                    y,
+                    char: keep_chars.then_some(char),
+                    color: None,
+                });
+            }
+        }
+        figure
+    }
+
+    /// Returns the figure resized by `x` and `y`, merging the points that
+    /// end up in the same cell. Merged points average their colours and keep
+    /// the first character.
+    pub fn scaled(&self, x: f32, y: f32) -> Self {
+        let mut cells: BTreeMap<(usize, usize), (FigurePoint, [u32; 3], u32)> = BTreeMap::new();
+        for point in &self.points {
+            let cell = (
+                (point.y as f32 * y).floor() as usize,
+                (point.x as f32 * x).floor() as usize,
+            );
+            let (_, sum, count) = cells.entry(cell).or_insert((*point, [0; 3], 0));
+            if let Some(Color::Rgb(r, g, b)) = point.color {
+                for (total, channel) in sum.iter_mut().zip([r, g, b]) {
+                    *total += u32::from(channel);
+                }
+                *count += 1;
+            }
+        }
+        let points = cells
+            .into_iter()
+            .map(|((y, x), (point, [r, g, b], count))| FigurePoint {
+                x,
+                y,
+                color: match count {
+                    0 => point.color,
+                    n => Some(Color::Rgb((r / n) as u8, (g / n) as u8, (b / n) as u8)),
+                },
+                ..point
+            })
+            .collect();
+        Self {
+            width: (self.width as f32 * x).ceil() as usize,
+            height: (self.height as f32 * y).ceil() as usize,
+            points,
+        }
+    }
+
+    /// Returns the figure shrunk to fit inside `area`, keeping its aspect
+    /// ratio, or unchanged if it already fits.
+    pub fn fitted(&self, area: Rect) -> Self {
+        let scale = (f32::from(area.width) / self.width.max(1) as f32)
+            .min(f32::from(area.height) / self.height.max(1) as f32);
+        if scale >= 1.0 {
+            return self.clone();
+        }
+        self.scaled(scale, scale)
+    }
+
+    /// Returns where each point lands with the figure centred in `area`,
+    /// relative to the area. Figures larger than the area overflow it, so
+    /// [`fitted`](Self::fitted) should come first.
+    pub fn placed(&self, area: Rect) -> Vec<Point2<f32>> {
+        let offset_x = (i32::from(area.width) - self.width as i32) / 2;
+        let offset_y = (i32::from(area.height) - self.height as i32) / 2;
+        self.points
+            .iter()
+            .map(|point| {
+                Point2::new(
+                    (offset_x + point.x as i32) as f32 + 0.5,
+                    (offset_y + point.y as i32) as f32 + 0.5,
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn figures_are_centred_and_shrink_to_fit() {
+        let figure = Figure::from_rows(&["#.#", ".#."], |c| c == '#', false);
+        assert_eq!(
+            (figure.width, figure.height, figure.points.len()),
+            (3, 2, 3)
+        );
+
+        let placed = figure.placed(Rect::new(0, 0, 7, 4));
+        assert_eq!(placed[0], Point2::new(2.5, 1.5));
+        assert_eq!(placed[2], Point2::new(3.5, 2.5));
+
+        let fitted = figure.fitted(Rect::new(0, 0, 2, 2));
+        assert!(fitted.width <= 2 && fitted.height <= 2);
+        let placed = fitted.placed(Rect::new(0, 0, 2, 2));
+        assert!(placed.iter().all(|pos| pos.x < 2.0 && pos.y < 2.0));
+        assert_eq!(figure.fitted(Rect::new(0, 0, 7, 4)), figure);
+    }
+
+    #[test]
+    fn merged_points_average_their_colours() {
+        let point = |x, color| FigurePoint {
+            x,
+            y: 0,
+            char: None,
+            color: Some(color),
+        };
+        let figure = Figure {
+            width: 2,
+            height: 1,
+            points: vec![
+                point(0, Color::Rgb(200, 0, 0)),
+                point(1, Color::Rgb(0, 0, 100)),
+            ],
+        };
+        let merged = figure.scaled(0.5, 1.0);
+        assert_eq!(merged.points.len(), 1);
+        assert_eq!(merged.points[0].color, Some(Color::Rgb(100, 0, 50)));
+    }
+}
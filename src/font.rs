@@ -0,0 +1,256 @@
+use crate::figure::Figure;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::{error, fs, io};
+
+/// Glyphs of the built-in font, five rows each; `#` is filled.
+const BUILTIN_GLYPHS: [(char, [&str; 5]); 56] = [
+    (' ', ["...", "...", "...", "...", "..."]),
+    ('A', [".###.", "#...#", "#####", "#...#", "#...#"]),
+    ('B', ["####.", "#...#", "####.", "#...#", "####."]),
+    ('C', [".####", "#....", "#....", "#....", ".####"]),
+    ('D', ["####.", "#...#", "#...#", "#...#", "####."]),
+    ('E', ["#####", "#....", "####.", "#....", "#####"]),
+    ('F', ["#####", "#....", "####.", "#....", "#...."]),
+    ('G', [".####", "#....", "#.###", "#...#", ".###."]),
+    ('H', ["#...#", "#...#", "#####", "#...#", "#...#"]),
+    ('I', ["#####", "..#..", "..#..", "..#..", "#####"]),
+    ('J', ["..###", "...#.", "...#.", "#..#.", ".##.."]),
+    ('K', ["#...#", "#..#.", "###..", "#..#.", "#...#"]),
+    ('L', ["#....", "#....", "#....", "#....", "#####"]),
+    ('M', ["#...#", "##.##", "#.#.#", "#...#", "#...#"]),
+    ('N', ["#...#", "##..#", "#.#.#", "#..##", "#...#"]),
+    ('O', [".###.", "#...#", "#...#", "#...#", ".###."]),
+    ('P', ["####.", "#...#", "####.", "#....", "#...."]),
+    ('Q', [".###.", "#...#", "#.#.#", "#..#.", ".##.#"]),
+    ('R', ["####.", "#...#", "####.", "#..#.", "#...#"]),
+    ('S', [".####", "#....", ".###.", "....#", "####."]),
+    ('T', ["#####", "..#..", "..#..", "..#..", "..#.."]),
+    ('U', ["#...#", "#...#", "#...#", "#...#", ".###."]),
+    ('V', ["#...#", "#...#", "#...#", ".#.#.", "..#.."]),
+    ('W', ["#...#", "#...#", "#.#.#", "##.##", "#...#"]),
+    ('X', ["#...#", ".#.#.", "..#..", ".#.#.", "#...#"]),
+    ('Y', ["#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+    ('Z', ["#####", "...#.", "..#..", ".#...", "#####"]),
+    ('0', [".###.", "#..##", "#.#.#", "##..#", ".###."]),
+    ('1', ["..#..", ".##..", "..#..", "..#..", ".###."]),
+    ('2', ["####.", "....#", ".###.", "#....", "#####"]),
+    ('3', ["####.", "....#", ".###.", "....#", "####."]),
+    ('4', ["#...#", "#...#", "#####", "....#", "....#"]),
+    ('5', ["#####", "#....", "####.", "....#", "####."]),
+    ('6', [".###.", "#....", "####.", "#...#", ".###."]),
+    ('7', ["#####", "....#", "...#.", "..#..", "..#.."]),
+    ('8', [".###.", "#...#", ".###.", "#...#", ".###."]),
+    ('9', [".###.", "#...#", ".####", "....#", ".###."]),
+    ('!', ["#", "#", "#", ".", "#"]),
+    ('?', [".###.", "#...#", "..##.", ".....", "..#.."]),
+    ('.', [".", ".", ".", ".", "#"]),
+    (',', ["..", "..", "..", ".#", "#."]),
+    (':', [".", "#", ".", "#", "."]),
+    (';', ["..", ".#", "..", ".#", "#."]),
+    ('-', ["...", "...", "###", "...", "..."]),
+    ('+', [".....", "..#..", ".###.", "..#..", "....."]),
+    ('=', ["....", "####", "....", "####", "...."]),
+    ('_', [".....", ".....", ".....", ".....", "#####"]),
+    ('\'', ["#", "#", ".", ".", "."]),
+    ('"', ["#.#", "#.#", "...", "...", "..."]),
+    ('(', [".#", "#.", "#.", "#.", ".#"]),
+    (')', ["#.", ".#", ".#", ".#", "#."]),
+    ('/', ["....#", "...#.", "..#..", ".#...", "#...."]),
+    ('*', ["#.#.#", ".###.", "#####", ".###.", "#.#.#"]),
+    ('#', [".#.#.", "#####", ".#.#.", "#####", ".#.#."]),
+    ('<', ["..#", ".#.", "#..", ".#.", "..#"]),
+    ('>', ["#..", ".#.", "..#", ".#.", "#.."]),
+];
+
+/// Error raised while loading a FIGlet font.
+#[derive(Debug)]
+pub enum FontError {
+    /// The font file could not be read.
+    Io(io::Error),
+    /// The font file is not in FIGlet format.
+    Invalid(String),
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FontError::Io(e) => write!(f, "cannot read font: {e}"),
+            FontError::Invalid(reason) => write!(f, "invalid font: {reason}"),
+        }
+    }
+}
+
+impl error::Error for FontError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            FontError::Io(e) => Some(e),
+            FontError::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for FontError {
+    fn from(e: io::Error) -> Self {
+        FontError::Io(e)
+    }
+}
+
+/// A font that turns text into a [`Figure`].
+///
+/// The built-in font is a 5x5 bitmap with capitals, digits and common
+/// punctuation; lower-case letters are drawn as capitals. FIGlet fonts keep
+/// their characters, so particles take on the look of the font once in
+/// place.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Font {
+    height: usize,
+    /// Rows of every glyph, with blank cells as spaces.
+    glyphs: HashMap<char, Vec<String>>,
+    /// Columns left between two glyphs.
+    spacing: usize,
+    /// Whether particles show the characters of the glyphs.
+    keep_chars: bool,
+}
+
+impl Default for Font {
+    fn default() -> Self {
+        let glyphs = BUILTIN_GLYPHS
+            .iter()
+            .map(|(char, rows)| {
+                let rows = rows.iter().map(|row| row.replace('.', " ")).collect();
+                (*char, rows)
+            })
+            .collect();
+        Self {
+            height: 5,
+            glyphs,
+            spacing: 1,
+            keep_chars: false,
+        }
+    }
+}
+
+impl Font {
+    /// Parses a FIGlet font.
+    ///
+    /// Only the layout information needed to draw characters side by side
+    /// is read; smushing rules are ignored.
+    pub fn from_flf(source: &str) -> Result<Self, FontError> {
+        let invalid = |reason: &str| FontError::Invalid(reason.to_string());
+        let mut lines = source.lines();
+        let header = lines.next().ok_or_else(|| invalid("empty file"))?;
+        let hardblank = header
+            .strip_prefix("flf2a")
+            .and_then(|rest| rest.chars().next())
+            .ok_or_else(|| invalid("missing `flf2a` signature"))?;
+        let fields: Vec<i64> = header
+            .split_whitespace()
+            .skip(1)
+            .map_while(|field| field.parse().ok())
+            .collect();
+        let (height, comment_lines) = match fields[..] {
+            [height, _, _, _, comment_lines, ..] if height > 0 && comment_lines >= 0 => {
+                (height as usize, comment_lines as usize)
+            }
+            _ => return Err(invalid("malformed header")),
+        };
+
+        let mut lines = lines.skip(comment_lines);
+        let mut glyphs = HashMap::new();
+        for code in 32..=126u8 {
+            let mut rows = Vec::with_capacity(height);
+            for _ in 0..height {
+                let line = lines.next().ok_or_else(|| {
+                    FontError::Invalid(format!("glyph {:?} is cut short", code as char))
+                })?;
+                let endmark = line.chars().last().unwrap_or('@');
+                let row = line.trim_end_matches(endmark).replace(hardblank, " ");
+                rows.push(row);
+            }
+            glyphs.insert(code as char, rows);
+        }
+        Ok(Self {
+            height,
+            glyphs,
+            spacing: 0,
+            keep_chars: true,
+        })
+    }
+
+    /// Reads and parses the FIGlet font at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, FontError> {
+        Self::from_flf(&fs::read_to_string(path)?)
+    }
+
+    /// Returns the rows of `char`, falling back to its capital and then to a
+    /// question mark.
+    fn glyph(&self, char: char) -> Option<&Vec<String>> {
+        self.glyphs
+            .get(&char)
+            .or_else(|| self.glyphs.get(&char.to_ascii_uppercase()))
+            .or_else(|| self.glyphs.get(&'?'))
+    }
+
+    /// Lays out `text`, which may span several lines, as a figure.
+    pub fn render(&self, text: &str) -> Figure {
+        let mut rows: Vec<String> = vec![];
+        for (index, line) in text.lines().enumerate() {
+            if index > 0 {
+                rows.push(String::new());
+            }
+            let mut line_rows = vec![String::new(); self.height];
+            for glyph in line.chars().filter_map(|char| self.glyph(char)) {
+                let width = glyph
+                    .iter()
+                    .map(|row| row.chars().count())
+                    .max()
+                    .unwrap_or(0);
+                for (row, glyph_row) in line_rows.iter_mut().zip(glyph) {
+                    let padding = width - glyph_row.chars().count() + self.spacing;
+                    row.push_str(glyph_row);
+                    row.push_str(&" ".repeat(padding));
+                }
+            }
+            rows.extend(line_rows);
+        }
+        Figure::from_rows(&rows, |char| char != ' ', self.keep_chars)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_font_draws_capitals_for_any_case() {
+        let font = Font::default();
+        let upper = font.render("HI!");
+        assert_eq!(upper, font.render("hi!"));
+        assert_eq!((upper.width, upper.height), (14, 5));
+        assert!(upper.points.iter().all(|point| point.char.is_none()));
+
+        let two_lines = font.render("A\nB");
+        assert_eq!(two_lines.height, 11);
+    }
+
+    #[test]
+    fn figlet_fonts_keep_their_characters() {
+        let mut source = String::from("flf2a$ 2 1 4 -1 1\nA tiny test font\n");
+        for code in 32..=126u8 {
+            match code as char {
+                'I' => source.push_str("|$@\n|$@@\n"),
+                _ => source.push_str("  @\n  @@\n"),
+            }
+        }
+        let font = Font::from_flf(&source).unwrap();
+        let figure = font.render("II");
+        assert_eq!((figure.width, figure.height), (4, 2));
+        assert_eq!(figure.points.len(), 4);
+        assert_eq!(figure.points[0].char, Some('|'));
+
+        assert!(Font::from_flf("flf2a$ 2 1 4 -1 0\n  @\n").is_err());
+        assert!(Font::from_flf("figlet 2 1 4 -1 0\n").is_err());
+    }
+}
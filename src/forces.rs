@@ -0,0 +1,223 @@
+use nalgebra::{Point2, Vector2};
+use std::time::Duration;
+
+/// A single contribution to a [`ForceField`].
+///
+/// Every force is expressed as an acceleration, so particles of any size
+/// respond to it in the same way.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Force {
+    /// Drag proportional to speed, `-k * v`.
+    LinearDrag(f32),
+    /// Drag proportional to the square of speed, `-k * |v| * v`.
+    QuadraticDrag(f32),
+    /// Velocity of the surrounding air. Drag acts on the velocity relative to
+    /// the wind, so wind has no effect on a field without drag.
+    Wind(Vector2<f32>),
+    /// Pulls particles toward `position`, or pushes them away when `strength`
+    /// is negative. Has no effect beyond `radius`.
+    Attractor {
+        position: Point2<f32>,
+        strength: f32,
+        radius: f32,
+    },
+    /// Smoothly varying pseudo-random acceleration. `scale` is the size of a
+    /// noise feature in cells and `speed` how fast the pattern evolves.
+    Turbulence {
+        strength: f32,
+        scale: f32,
+        speed: f32,
+    },
+}
+
+/// The set of forces acting on every particle of a simulation, in addition to
+/// gravity.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ForceField {
+    forces: Vec<Force>,
+    time: f32,
+    seed: u32,
+}
+
+impl ForceField {
+    /// Constructs an empty [`ForceField`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the field with `force` added.
+    pub fn with(mut self, force: Force) -> Self {
+        self.push(force);
+        self
+    }
+
+    /// Returns the field with its turbulence pattern derived from `seed`.
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.set_seed(seed);
+        self
+    }
+
+    /// Derives the turbulence pattern from `seed`.
+    pub fn set_seed(&mut self, seed: u32) {
+        self.seed = seed;
+    }
+
+    /// Adds `force` to the field.
+    pub fn push(&mut self, force: Force) {
+        self.forces.push(force);
+    }
+
+    /// Removes every force.
+    pub fn clear(&mut self) {
+        self.forces.clear();
+    }
+
+    pub fn forces(&self) -> &[Force] {
+        &self.forces
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.forces.is_empty()
+    }
+
+    /// Advances time-dependent forces such as turbulence.
+    pub fn advance(&mut self, dt: Duration) {
+        self.time += dt.as_secs_f32();
+    }
+
+    /// Returns the acceleration felt by a particle at `pos` moving at `vel`.
+    pub fn acceleration(&self, pos: Point2<f32>, vel: Vector2<f32>) -> Vector2<f32> {
+        let wind: Vector2<f32> = self
+            .forces
+            .iter()
+            .filter_map(|force| match force {
+                Force::Wind(wind) => Some(*wind),
+                _ => None,
+            })
+            .sum();
+        let relative_vel = vel - wind;
+
+        let mut acc = Vector2::zeros();
+        for force in &self.forces {
+            match *force {
+                Force::LinearDrag(k) => acc -= relative_vel * k,
+                Force::QuadraticDrag(k) => acc -= relative_vel * (k * relative_vel.norm()),
+                Force::Wind(_) => {}
+                Force::Attractor {
+                    position,
+                    strength,
+                    radius,
+                } => {
+                    let offset = position - pos;
+                    let distance = offset.norm();
+                    if distance > f32::EPSILON && distance < radius {
+                        // Falls off linearly so there is no singularity at
+                        // the centre and no jump at the edge.
+                        acc += offset / distance * strength * (1.0 - distance / radius);
+                    }
+                }
+                Force::Turbulence {
+                    strength,
+                    scale,
+                    speed,
+                } => {
+                    let x = pos.x / scale.max(f32::EPSILON);
+                    let y = pos.y / scale.max(f32::EPSILON);
+                    let t = self.time * speed;
+                    acc += Vector2::new(
+                        value_noise(x + t, y, self.seed),
+                        value_noise(x, y + t, self.seed.wrapping_add(1)),
+                    ) * strength;
+                }
+            }
+        }
+        acc
+    }
+}
+
+// Hashes a lattice point to a value in [-1, 1].
+fn lattice(x: i32, y: i32, seed: u32) -> f32 {
+    let mut h = seed
+        .wrapping_add((x as u32).wrapping_mul(0x27d4_eb2d))
+        .wrapping_add((y as u32).wrapping_mul(0x1656_67b1));
+    h = (h ^ (h >> 15)).wrapping_mul(0x85eb_ca6b);
+    h = (h ^ (h >> 13)).wrapping_mul(0xc2b2_ae35);
+    h ^= h >> 16;
+    h as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
+
+// Smoothly interpolated 2D value noise in [-1, 1].
+fn value_noise(x: f32, y: f32, seed: u32) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let (sx, sy) = (fx * fx * (3.0 - 2.0 * fx), fy * fy * (3.0 - 2.0 * fy));
+    let (ix, iy) = (x0 as i32, y0 as i32);
+
+    let top = lattice(ix, iy, seed) * (1.0 - sx) + lattice(ix + 1, iy, seed) * sx;
+    let bottom = lattice(ix, iy + 1, seed) * (1.0 - sx) + lattice(ix + 1, iy + 1, seed) * sx;
+    top * (1.0 - sy) + bottom * sy
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drag_opposes_motion_relative_to_wind() {
+        let field = ForceField::new()
+            .with(Force::LinearDrag(1.0))
+            .with(Force::Wind(Vector2::new(5.0, 0.0)));
+
+        let still = field.acceleration(Point2::origin(), Vector2::zeros());
+        assert_eq!(still, Vector2::new(5.0, 0.0));
+
+        let with_wind = field.acceleration(Point2::origin(), Vector2::new(5.0, 0.0));
+        assert_eq!(with_wind, Vector2::zeros());
+    }
+
+    #[test]
+    fn attractor_and_repulsor_point_opposite_ways() {
+        let attractor = Force::Attractor {
+            position: Point2::new(10.0, 0.0),
+            strength: 4.0,
+            radius: 20.0,
+        };
+        let pull = ForceField::new()
+            .with(attractor)
+            .acceleration(Point2::origin(), Vector2::zeros());
+        assert!(pull.x > 0.0 && pull.y == 0.0);
+
+        let repulsor = Force::Attractor {
+            position: Point2::new(10.0, 0.0),
+            strength: -4.0,
+            radius: 20.0,
+        };
+        let push = ForceField::new()
+            .with(repulsor)
+            .acceleration(Point2::origin(), Vector2::zeros());
+        assert_eq!(push, -pull);
+
+        let outside = ForceField::new()
+            .with(attractor)
+            .acceleration(Point2::new(40.0, 0.0), Vector2::zeros());
+        assert_eq!(outside, Vector2::zeros());
+    }
+
+    #[test]
+    fn turbulence_is_bounded_and_evolves_over_time() {
+        let mut field = ForceField::new().with(Force::Turbulence {
+            strength: 3.0,
+            scale: 4.0,
+            speed: 1.0,
+        });
+        let pos = Point2::new(7.3, 2.9);
+        let before = field.acceleration(pos, Vector2::zeros());
+        field.advance(Duration::from_millis(500));
+        let after = field.acceleration(pos, Vector2::zeros());
+
+        assert_ne!(before, after);
+        for acc in [before, after] {
+            assert!(acc.x.abs() <= 3.0 && acc.y.abs() <= 3.0);
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use crate::app::Mode;
+use crate::consts::CHARACTERS;
+use crate::trail::TRAIL_RAMP;
+use std::fmt;
+use std::str::FromStr;
+
+/// The characters one simulation draws its particles with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Glyphs {
+    /// Confetti pieces, one picked at random per particle.
+    pub pieces: Vec<char>,
+    /// Head of a rocket or shooting star.
+    pub head: char,
+    /// Tail drawn behind a rocket or shooting star.
+    pub tail: char,
+    /// Characters a spark burns through over its life.
+    pub sparks: Vec<char>,
+    /// Characters an afterglow cell fades through.
+    pub trail: Vec<char>,
+}
+
+/// A family of [`Glyphs`], with a variant suited to each simulation.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub enum GlyphSet {
+    /// Block elements and dots, which most modern fonts have.
+    #[default]
+    Unicode,
+    /// Printable 7-bit characters only, for consoles and old fonts.
+    Ascii,
+    /// Double-width emoji.
+    Emoji,
+    /// Every particle is drawn from these characters. Heads use the first
+    /// and tails the last.
+    Custom(Vec<char>),
+}
+
+impl GlyphSet {
+    /// Returns the glyphs of this set for the simulation `mode`.
+    pub fn for_mode(&self, mode: Mode) -> Glyphs {
+        let glyphs = |pieces: &[char], head, tail, sparks: &[char], trail: &[char]| Glyphs {
+            pieces: pieces.to_vec(),
+            head,
+            tail,
+            sparks: sparks.to_vec(),
+            trail: trail.to_vec(),
+        };
+        match (self, mode) {
+            (GlyphSet::Unicode, Mode::Stars) => {
+                glyphs(&CHARACTERS, '●', '·', &['*', '+', '·'], &TRAIL_RAMP)
+            }
+            (GlyphSet::Unicode, _) => glyphs(&CHARACTERS, '▄', '│', &['*', '+', '·'], &TRAIL_RAMP),
+            (GlyphSet::Ascii, Mode::Stars) => glyphs(
+                &['#', '%', '=', '+', 'o', 'x'],
+                'O',
+                '.',
+                &['*', '+', '.'],
+                &['o', ':', '.'],
+            ),
+            (GlyphSet::Ascii, _) => glyphs(
+                &['#', '%', '=', '+', 'o', 'x'],
+                '^',
+                '|',
+                &['*', '+', '.'],
+                &['o', ':', '.'],
+            ),
+            (GlyphSet::Emoji, Mode::Stars) => glyphs(
+                &['🎉', '🎊', '🎈', '✨', '💖'],
+                '🌠',
+                '·',
+                &['🌟', '✨', '·'],
+                &TRAIL_RAMP,
+            ),
+            (GlyphSet::Emoji, _) => glyphs(
+                &['🎉', '🎊', '🎈', '✨', '💖'],
+                '🚀',
+                '│',
+                &['🎆', '✨', '·'],
+                &TRAIL_RAMP,
+            ),
+            (GlyphSet::Custom(chars), _) => {
+                let first = chars.first().copied().unwrap_or('*');
+                let last = chars.last().copied().unwrap_or('*');
+                glyphs(chars, first, last, chars, chars)
+            }
+        }
+    }
+}
+
+impl fmt::Display for GlyphSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GlyphSet::Unicode => f.write_str("unicode"),
+            GlyphSet::Ascii => f.write_str("ascii"),
+            GlyphSet::Emoji => f.write_str("emoji"),
+            GlyphSet::Custom(chars) => f.write_str(&chars.iter().collect::<String>()),
+        }
+    }
+}
+
+/// Parses `unicode`, `ascii` or `emoji`, and any other string as the
+/// characters of a [`GlyphSet::Custom`] set.
+impl FromStr for GlyphSet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unicode" => Ok(GlyphSet::Unicode),
+            "ascii" => Ok(GlyphSet::Ascii),
+            "emoji" => Ok(GlyphSet::Emoji),
+            "" => Err("a custom glyph set needs at least one character".to_string()),
+            _ => Ok(GlyphSet::Custom(s.chars().collect())),
+        }
+    }
+}
+
+/// Returns a printable 7-bit stand-in for `c`, picked to look alike where
+/// possible.
+pub fn to_ascii(c: char) -> char {
+    match c {
+        ' '..='~' => c,
+        '█' | '▓' => '#',
+        '▒' => '%',
+        '░' => ':',
+        '▀' => '\'',
+        '▄' | '·' | '∙' | '•' => '.',
+        '│' | '┃' => '|',
+        '─' | '━' => '-',
+        '●' | '○' | '◉' => 'o',
+        '\u{2800}'..='\u{28ff}' => match (u32::from(c) - 0x2800).count_ones() {
+            0 => ' ',
+            1 => '.',
+            2 => ':',
+            3 | 4 => '+',
+            _ => '#',
+        },
+        _ => '*',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use unicode_width::UnicodeWidthChar;
+
+    #[test]
+    fn ascii_sets_are_seven_bit() {
+        for mode in [Mode::Confetti, Mode::Fireworks, Mode::Stars] {
+            let glyphs = GlyphSet::Ascii.for_mode(mode);
+            let chars = glyphs
+                .pieces
+                .iter()
+                .chain(&glyphs.sparks)
+                .chain(&glyphs.trail)
+                .chain([&glyphs.head, &glyphs.tail]);
+            for c in chars {
+                assert!(c.is_ascii_graphic() || *c == ' ', "{c:?} is not ASCII");
+            }
+        }
+        for c in ['█', '▀', '│', '●', '⣿', '⠁', '🎉'] {
+            assert!(to_ascii(c).is_ascii(), "{c:?}");
+        }
+        assert_eq!(to_ascii('⠉'), ':');
+    }
+
+    #[test]
+    fn emoji_are_double_width() {
+        let glyphs = GlyphSet::Emoji.for_mode(Mode::Fireworks);
+        assert!(glyphs.pieces.iter().all(|c| c.width() == Some(2)));
+        assert_eq!(glyphs.head.width(), Some(2));
+    }
+
+    #[test]
+    fn other_strings_are_custom_sets() {
+        assert_eq!("ascii".parse(), Ok(GlyphSet::Ascii));
+        let custom: GlyphSet = "*o.".parse().unwrap();
+        let glyphs = custom.for_mode(Mode::Stars);
+        assert_eq!((glyphs.head, glyphs.tail), ('*', '.'));
+        assert_eq!(custom.to_string(), "*o.");
+        assert!("".parse::<GlyphSet>().is_err());
+    }
+}
@@ -1,22 +1,54 @@
-use crate::app::{App, AppResult};
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::app::{App, AppResult, Mode};
+use crate::consts::{INTENSITY_STEP, SPEED_STEP};
+use crate::keymap::Action;
+use crossterm::event::{KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 
 /// Handles the key events and updates the state of [`App`].
+///
+/// Keys are looked up in the [`KeyMap`](crate::keymap::KeyMap) of the app;
+/// any key it does not bind spawns a burst.
 pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
-    match key_event.code {
-        // Exit application on `ESC` or `q`
-        KeyCode::Esc | KeyCode::Char('q') => {
-            app.quit();
-        }
-        // Exit application on `Ctrl-C`
-        KeyCode::Char('c') | KeyCode::Char('C') => {
-            if key_event.modifiers == KeyModifiers::CONTROL {
-                app.quit();
+    match app.keymap.action(&key_event).unwrap_or(Action::Spawn) {
+        Action::Quit => app.quit(),
+        Action::Help => app.show_help = !app.show_help,
+        Action::Spawn => app.spawn_particles(),
+        Action::Pause => app.paused = !app.paused,
+        Action::Step => app.step(),
+        Action::Clear => app.clear(),
+        Action::Faster => app.scale_speed(SPEED_STEP),
+        Action::Slower => app.scale_speed(1.0 / SPEED_STEP),
+        Action::More => app.scale_intensity(INTENSITY_STEP),
+        Action::Fewer => app.scale_intensity(1.0 / INTENSITY_STEP),
+        Action::Confetti => app.switch_mode(Mode::Confetti),
+        Action::Fireworks => app.switch_mode(Mode::Fireworks),
+        Action::Stars => app.switch_mode(Mode::Stars),
+        Action::CarryOver => app.carry_over = !app.carry_over,
+    }
+    Ok(())
+}
+
+/// Handles the mouse events and updates the state of [`App`].
+///
+/// A left click spawns a burst at the cursor, dragging keeps spawning along
+/// the way, and scrolling turns the intensity up or down.
+pub fn handle_mouse_events(mouse_event: MouseEvent, app: &mut App) -> AppResult<()> {
+    let target = app.cell_to_point(mouse_event.column, mouse_event.row);
+    match mouse_event.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            if let Some(target) = target {
+                app.spawn_at(target);
             }
+            app.pointer = target;
+        }
+        MouseEventKind::Drag(MouseButton::Left) => {
+            app.pointer = target;
         }
-        _ => {
-            app.spawn_particles();
+        MouseEventKind::Up(MouseButton::Left) => {
+            app.pointer = None;
         }
+        MouseEventKind::ScrollUp => app.scale_intensity(INTENSITY_STEP),
+        MouseEventKind::ScrollDown => app.scale_intensity(1.0 / INTENSITY_STEP),
+        _ => {}
     }
     Ok(())
 }
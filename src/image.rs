@@ -0,0 +1,320 @@
+use crate::figure::{Figure, FigurePoint};
+use ratatui::style::Color;
+use std::fmt;
+use std::path::Path;
+use std::{error, fs, io};
+
+/// Character image pixels are drawn with once in place.
+const PIXEL: char = '█';
+
+/// How far apart, per channel, two colours may be and still count as the
+/// same background.
+const BACKGROUND_TOLERANCE: u8 = 16;
+
+/// First bytes of every PNG image.
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Error raised while loading an image.
+#[derive(Debug)]
+pub enum ImageError {
+    /// The image could not be read.
+    Io(io::Error),
+    /// The image is damaged or not in the format it claims to be.
+    Invalid(String),
+    /// The image is in a format that cannot be decoded.
+    Unsupported(String),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::Io(e) => write!(f, "cannot read image: {e}"),
+            ImageError::Invalid(reason) => write!(f, "invalid image: {reason}"),
+            ImageError::Unsupported(reason) => write!(f, "unsupported image: {reason}"),
+        }
+    }
+}
+
+impl error::Error for ImageError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ImageError::Io(e) => Some(e),
+            ImageError::Invalid(_) | ImageError::Unsupported(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for ImageError {
+    fn from(e: io::Error) -> Self {
+        ImageError::Io(e)
+    }
+}
+
+/// Reads the image at `path` as a figure, telling a PNG or PPM image from
+/// ASCII art by its contents.
+pub fn load(path: impl AsRef<Path>) -> Result<Figure, ImageError> {
+    let bytes = fs::read(path)?;
+    if bytes.starts_with(PNG_SIGNATURE) {
+        return from_png(&bytes);
+    }
+    if bytes.starts_with(b"P3") || bytes.starts_with(b"P6") {
+        return from_ppm(&bytes);
+    }
+    match String::from_utf8(bytes) {
+        Ok(text) => Ok(from_ascii_art(&text)),
+        Err(_) => Err(ImageError::Unsupported(
+            "expected a PNG or PPM image or a text file".to_string(),
+        )),
+    }
+}
+
+/// Turns ASCII art into a figure in which every character that is not
+/// whitespace is a point that keeps its character.
+pub fn from_ascii_art(text: &str) -> Figure {
+    let rows: Vec<&str> = text.lines().collect();
+    Figure::from_rows(&rows, |char| !char.is_whitespace(), true)
+}
+
+/// Decodes a PNG image into a figure, as [`from_ppm`] does. Pixels that are
+/// more than half transparent are background too.
+pub fn from_png(bytes: &[u8]) -> Result<Figure, ImageError> {
+    let invalid = |e: png::DecodingError| ImageError::Invalid(e.to_string());
+    let mut decoder = png::Decoder::new(bytes);
+    // Palettes, low bit depths and 16-bit samples all become 8-bit channels.
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(invalid)?;
+    let (width, height) = reader.info().size();
+    let (color_type, _) = reader.output_color_type();
+
+    // Rows are read one at a time, so a header claiming a huge image costs
+    // nothing until its data turns up.
+    let mut pixels = vec![];
+    while let Some(row) = reader.next_row().map_err(invalid)? {
+        let opaque = |alpha: u8| alpha >= 128;
+        let row = row.data();
+        match color_type {
+            png::ColorType::Grayscale => {
+                pixels.extend(row.iter().map(|&l| Some([l, l, l])));
+            }
+            png::ColorType::GrayscaleAlpha => pixels.extend(
+                row.chunks_exact(2)
+                    .map(|la| opaque(la[1]).then_some([la[0], la[0], la[0]])),
+            ),
+            png::ColorType::Rgb => {
+                pixels.extend(
+                    row.chunks_exact(3)
+                        .map(|rgb| Some([rgb[0], rgb[1], rgb[2]])),
+                );
+            }
+            png::ColorType::Rgba => pixels.extend(
+                row.chunks_exact(4)
+                    .map(|rgba| opaque(rgba[3]).then_some([rgba[0], rgba[1], rgba[2]])),
+            ),
+            png::ColorType::Indexed => {
+                return Err(ImageError::Unsupported(
+                    "indexed colour without a palette".to_string(),
+                ))
+            }
+        }
+    }
+    Ok(from_pixels(width as usize, height as usize, &pixels))
+}
+
+/// Decodes a PPM image, plain (`P3`) or binary (`P6`), into a figure with a
+/// point of the pixel's colour for every pixel that differs from the
+/// background, taken to be the colour of the top-left pixel.
+///
+/// Terminal cells are about twice as tall as they are wide, so every two
+/// rows of pixels become one row of the figure.
+pub fn from_ppm(bytes: &[u8]) -> Result<Figure, ImageError> {
+    let invalid = |reason: &str| ImageError::Invalid(reason.to_string());
+    let mut header = Header { bytes, offset: 0 };
+    let magic = header
+        .next()
+        .ok_or_else(|| invalid("missing magic number"))?;
+    let mut field = |name: &str| {
+        header
+            .next()
+            .and_then(|token| std::str::from_utf8(token).ok()?.parse::<usize>().ok())
+            .ok_or_else(|| ImageError::Invalid(format!("missing or malformed {name}")))
+    };
+    let width = field("width")?;
+    let height = field("height")?;
+    let max = field("maximum value")?;
+    if max == 0 || max > usize::from(u16::MAX) {
+        return Err(invalid("maximum value out of range"));
+    }
+
+    let count = width
+        .checked_mul(height)
+        .and_then(|pixels| pixels.checked_mul(3))
+        .ok_or_else(|| invalid("image dimensions are too large"))?;
+    let samples: Vec<usize> = match magic {
+        b"P3" => header
+            .by_ref()
+            .take(count)
+            .map(|token| std::str::from_utf8(token).ok()?.parse().ok())
+            .collect::<Option<_>>()
+            .ok_or_else(|| invalid("malformed sample"))?,
+        b"P6" => {
+            // A single whitespace byte separates the header from the raster.
+            let raster = bytes.get(header.offset + 1..).unwrap_or_default();
+            if max < 256 {
+                raster.iter().take(count).map(|&b| usize::from(b)).collect()
+            } else {
+                raster
+                    .chunks_exact(2)
+                    .take(count)
+                    .map(|pair| usize::from(u16::from_be_bytes([pair[0], pair[1]])))
+                    .collect()
+            }
+        }
+        _ => return Err(invalid("expected a P3 or P6 image")),
+    };
+    if samples.len() < count {
+        return Err(invalid("image data is cut short"));
+    }
+
+    let to_byte = |sample: usize| (sample.min(max) * 255 / max) as u8;
+    let pixels: Vec<_> = samples
+        .chunks_exact(3)
+        .map(|rgb| Some([to_byte(rgb[0]), to_byte(rgb[1]), to_byte(rgb[2])]))
+        .collect();
+    Ok(from_pixels(width, height, &pixels))
+}
+
+/// Turns rows of pixels into a figure with a point for every pixel that is
+/// neither transparent, i.e. `None`, nor close to the colour of the top-left
+/// pixel.
+fn from_pixels(width: usize, height: usize, pixels: &[Option<[u8; 3]>]) -> Figure {
+    let background = pixels.first().copied().flatten();
+    let points = pixels
+        .iter()
+        .enumerate()
+        .filter_map(|(index, pixel)| {
+            let [r, g, b] = (*pixel)?;
+            if background.is_some_and(|background| similar([r, g, b], background)) {
+                return None;
+            }
+            Some(FigurePoint {
+                x: index % width,
+                y: index / width,
+                char: Some(PIXEL),
+                color: Some(Color::Rgb(r, g, b)),
+            })
+        })
+        .collect();
+    let figure = Figure {
+        width,
+        height,
+        points,
+    };
+    figure.scaled(1.0, 0.5)
+}
+
+/// Returns whether two colours are close enough to count as one.
+fn similar(a: [u8; 3], b: [u8; 3]) -> bool {
+    a.iter()
+        .zip(b)
+        .all(|(a, b)| a.abs_diff(b) <= BACKGROUND_TOLERANCE)
+}
+
+/// Splits the header of a PPM image into whitespace-separated tokens,
+/// skipping `#` comments.
+struct Header<'a> {
+    bytes: &'a [u8],
+    /// Index just past the last token returned.
+    offset: usize,
+}
+
+impl<'a> Iterator for Header<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let rest = self.bytes.get(self.offset..)?;
+            let start = rest.iter().position(|b| !b.is_ascii_whitespace())?;
+            if rest[start] == b'#' {
+                let end = rest[start..]
+                    .iter()
+                    .position(|&b| b == b'\n')
+                    .map_or(rest.len(), |end| start + end);
+                self.offset += end;
+                continue;
+            }
+            let len = rest[start..]
+                .iter()
+                .position(u8::is_ascii_whitespace)
+                .unwrap_or(rest.len() - start);
+            self.offset += start + len;
+            return Some(&rest[start..start + len]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ppm_pixels_keep_their_colour() {
+        let plain = b"P3\n# two by two\n2 2\n255\n0 0 0  255 0 0\n0 0 0  0 0 255\n";
+        let figure = from_ppm(plain).unwrap();
+        assert_eq!((figure.width, figure.height), (2, 1));
+        assert_eq!(figure.points.len(), 1);
+        // Red and blue share a cell once the rows are merged.
+        assert_eq!(figure.points[0].color, Some(Color::Rgb(127, 0, 127)));
+
+        let mut binary = b"P6 2 1 255\n".to_vec();
+        binary.extend([9, 9, 9, 0, 200, 0]);
+        let figure = from_ppm(&binary).unwrap();
+        assert_eq!(figure.points.len(), 1);
+        assert_eq!(figure.points[0].x, 1);
+        assert_eq!(figure.points[0].color, Some(Color::Rgb(0, 200, 0)));
+
+        assert!(from_ppm(b"P6 2 1 255\n\x00\x00").is_err());
+        assert!(from_ppm(b"P5 1 1 255\n\x00").is_err());
+    }
+
+    #[test]
+    fn oversized_ppm_headers_are_rejected() {
+        let huge = format!("P6 {} 2 255\n\x00", usize::MAX / 2);
+        assert!(matches!(
+            from_ppm(huge.as_bytes()),
+            Err(ImageError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn png_pixels_keep_their_colour_unless_transparent() {
+        let mut bytes = vec![];
+        let mut encoder = png::Encoder::new(&mut bytes, 3, 1);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer
+            .write_image_data(&[0, 0, 0, 255, 0, 200, 0, 255, 255, 0, 0, 0])
+            .unwrap();
+        writer.finish().unwrap();
+
+        let figure = from_png(&bytes).unwrap();
+        assert_eq!((figure.width, figure.height), (3, 1));
+        assert_eq!(figure.points.len(), 1);
+        assert_eq!(figure.points[0].x, 1);
+        assert_eq!(figure.points[0].color, Some(Color::Rgb(0, 200, 0)));
+
+        assert!(matches!(
+            from_png(&bytes[..bytes.len() / 2]),
+            Err(ImageError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn ascii_art_keeps_its_characters() {
+        let figure = from_ascii_art(" /\\\n/__\\\n");
+        assert_eq!((figure.width, figure.height), (4, 2));
+        assert_eq!(figure.points.len(), 6);
+        assert_eq!(figure.points[0].char, Some('/'));
+        assert!(figure.points.iter().all(|point| point.color.is_none()));
+    }
+}
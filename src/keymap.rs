@@ -0,0 +1,326 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::{error, fs, io};
+
+/// Something a key can be bound to.
+///
+/// Actions are listed in the help overlay in the order they are declared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    Quit,
+    Help,
+    Spawn,
+    Pause,
+    Step,
+    Clear,
+    Faster,
+    Slower,
+    More,
+    Fewer,
+    Confetti,
+    Fireworks,
+    Stars,
+    CarryOver,
+}
+
+impl Action {
+    /// Returns what the action does, as shown in the help overlay.
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Help => "show or hide this help",
+            Action::Spawn => "spawn a burst",
+            Action::Pause => "pause or resume",
+            Action::Step => "advance one tick while paused",
+            Action::Clear => "remove every particle",
+            Action::Faster => "speed time up",
+            Action::Slower => "slow time down",
+            Action::More => "spawn more particles per burst",
+            Action::Fewer => "spawn fewer particles per burst",
+            Action::Confetti => "switch to confetti",
+            Action::Fireworks => "switch to fireworks",
+            Action::Stars => "switch to shooting stars",
+            Action::CarryOver => "keep or clear particles when switching",
+        }
+    }
+}
+
+/// A key together with the modifiers that must be held, written like
+/// `q`, `space`, `ctrl-c` or `f1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl Key {
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    pub const fn char(c: char) -> Self {
+        Self::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    /// Whether `event` is this key. Shift is ignored for characters, since
+    /// it is already part of the character typed.
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        let ignored = match event.code {
+            KeyCode::Char(_) => KeyModifiers::SHIFT,
+            _ => KeyModifiers::NONE,
+        };
+        self.code == event.code && self.modifiers - ignored == event.modifiers - ignored
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl-"),
+            (KeyModifiers::ALT, "alt-"),
+            (KeyModifiers::SHIFT, "shift-"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(n) => write!(f, "f{n}"),
+            KeyCode::Enter => f.write_str("enter"),
+            KeyCode::Esc => f.write_str("esc"),
+            KeyCode::Tab => f.write_str("tab"),
+            KeyCode::Backspace => f.write_str("backspace"),
+            KeyCode::Up => f.write_str("up"),
+            KeyCode::Down => f.write_str("down"),
+            KeyCode::Left => f.write_str("left"),
+            KeyCode::Right => f.write_str("right"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        'prefixes: loop {
+            for (name, modifier) in [
+                ("ctrl-", KeyModifiers::CONTROL),
+                ("alt-", KeyModifiers::ALT),
+                ("shift-", KeyModifiers::SHIFT),
+            ] {
+                match rest.strip_prefix(name) {
+                    Some(key) if !key.is_empty() => {
+                        modifiers |= modifier;
+                        rest = key;
+                        continue 'prefixes;
+                    }
+                    _ => {}
+                }
+            }
+            break;
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest {
+                "space" => KeyCode::Char(' '),
+                "enter" => KeyCode::Enter,
+                "esc" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backspace" => KeyCode::Backspace,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                _ => match rest.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key `{s}`")),
+                },
+            },
+        };
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl TryFrom<String> for Key {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Error raised while loading a key map.
+#[derive(Debug)]
+pub enum KeyMapError {
+    /// The key map file could not be read.
+    Io(io::Error),
+    /// The key map is not valid TOML, names an unknown action or key.
+    Parse(toml::de::Error),
+}
+
+impl fmt::Display for KeyMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyMapError::Io(e) => write!(f, "cannot read key map: {e}"),
+            KeyMapError::Parse(e) => write!(f, "cannot parse key map: {e}"),
+        }
+    }
+}
+
+impl error::Error for KeyMapError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            KeyMapError::Io(e) => Some(e),
+            KeyMapError::Parse(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for KeyMapError {
+    fn from(e: io::Error) -> Self {
+        KeyMapError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for KeyMapError {
+    fn from(e: toml::de::Error) -> Self {
+        KeyMapError::Parse(e)
+    }
+}
+
+/// Which keys trigger which [`Action`].
+///
+/// Key map files are TOML tables from action names to lists of keys, e.g.
+/// `pause = ["p", "space"]`. Actions they leave out keep their default keys.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyMap {
+    bindings: BTreeMap<Action, Vec<Key>>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        let ctrl_c = Key::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        let enter = Key::new(KeyCode::Enter, KeyModifiers::NONE);
+        let esc = Key::new(KeyCode::Esc, KeyModifiers::NONE);
+        let bindings = [
+            (Action::Quit, vec![Key::char('q'), esc, ctrl_c]),
+            (Action::Help, vec![Key::char('?'), Key::char('h')]),
+            (Action::Spawn, vec![enter]),
+            (Action::Pause, vec![Key::char(' '), Key::char('p')]),
+            (Action::Step, vec![Key::char('.')]),
+            (Action::Clear, vec![Key::char('c')]),
+            (Action::Faster, vec![Key::char(']')]),
+            (Action::Slower, vec![Key::char('[')]),
+            (Action::More, vec![Key::char('+'), Key::char('=')]),
+            (Action::Fewer, vec![Key::char('-')]),
+            (Action::Confetti, vec![Key::char('1')]),
+            (Action::Fireworks, vec![Key::char('2')]),
+            (Action::Stars, vec![Key::char('3')]),
+            (Action::CarryOver, vec![Key::char('o')]),
+        ];
+        Self {
+            bindings: bindings.into_iter().collect(),
+        }
+    }
+}
+
+impl KeyMap {
+    /// Parses a key map from TOML, on top of the default bindings.
+    pub fn from_toml(source: &str) -> Result<Self, KeyMapError> {
+        let overrides: BTreeMap<Action, Vec<Key>> = toml::from_str(source)?;
+        let mut keymap = Self::default();
+        for (action, keys) in overrides {
+            keymap.bind(action, keys);
+        }
+        Ok(keymap)
+    }
+
+    /// Reads and parses the key map file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, KeyMapError> {
+        Self::from_toml(&fs::read_to_string(path)?)
+    }
+
+    /// Binds `keys` to `action` instead of its current keys, taking them
+    /// away from any other action.
+    pub fn bind(&mut self, action: Action, keys: Vec<Key>) {
+        for bound in self.bindings.values_mut() {
+            bound.retain(|key| !keys.contains(key));
+        }
+        self.bindings.insert(action, keys);
+    }
+
+    /// Returns the action `event` is bound to, if any.
+    pub fn action(&self, event: &KeyEvent) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.iter().any(|key| key.matches(event)))
+            .map(|(action, _)| *action)
+    }
+
+    /// Returns every action with the keys bound to it, in declaration order.
+    pub fn bindings(&self) -> impl Iterator<Item = (Action, &[Key])> {
+        self.bindings
+            .iter()
+            .map(|(action, keys)| (*action, keys.as_slice()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn keys_round_trip_through_their_names() {
+        for name in ["q", "space", "ctrl-c", "alt-shift-x", "f5", "-", "ctrl--"] {
+            let key: Key = name.parse().unwrap();
+            assert_eq!(key.to_string(), name);
+        }
+        assert!("hyper-x".parse::<Key>().is_err());
+        assert!("f13".parse::<Key>().is_err());
+    }
+
+    #[test]
+    fn shift_is_ignored_for_characters() {
+        let keymap = KeyMap::default();
+        let question = press(KeyCode::Char('?'), KeyModifiers::SHIFT);
+        assert_eq!(keymap.action(&question), Some(Action::Help));
+
+        let ctrl_c = press(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(keymap.action(&ctrl_c), Some(Action::Quit));
+        let c = press(KeyCode::Char('c'), KeyModifiers::NONE);
+        assert_eq!(keymap.action(&c), Some(Action::Clear));
+    }
+
+    #[test]
+    fn files_rebind_on_top_of_the_defaults() {
+        let keymap = KeyMap::from_toml("pause = [\"q\"]\nstars = [\"s\", \"f3\"]").unwrap();
+        let q = press(KeyCode::Char('q'), KeyModifiers::NONE);
+        assert_eq!(keymap.action(&q), Some(Action::Pause));
+        let f3 = press(KeyCode::F(3), KeyModifiers::NONE);
+        assert_eq!(keymap.action(&f3), Some(Action::Stars));
+        let space = press(KeyCode::Char(' '), KeyModifiers::NONE);
+        assert_eq!(keymap.action(&space), None);
+        let esc = press(KeyCode::Esc, KeyModifiers::NONE);
+        assert_eq!(keymap.action(&esc), Some(Action::Quit));
+
+        assert!(KeyMap::from_toml("launch = [\"l\"]").is_err());
+        assert!(KeyMap::from_toml("pause = [\"hyper-p\"]").is_err());
+    }
+}
@@ -13,12 +13,63 @@ pub mod tui;
 /// Event handler.
 pub mod handler;
 
+/// Configurable key bindings.
+pub mod keymap;
+
 /// Projectile Physics.
 pub mod projectile;
 
+/// Force fields.
+pub mod forces;
+
+/// Particle lifetimes.
+pub mod lifetime;
+
 // Stateful Widget
 pub mod system;
 
+/// Clipped cell drawing.
+pub mod render;
+
+/// Colour helpers.
+pub mod color;
+
+/// Colour palettes and terminal colour support.
+pub mod palette;
+
+/// Glyph sets.
+pub mod glyphs;
+
+/// Shapes for particles to assemble into.
+pub mod figure;
+
+/// Bitmap and FIGlet fonts.
+pub mod font;
+
+/// Images and ASCII art.
+pub mod image;
+
+/// Particle afterglow.
+pub mod trail;
+
+/// Particle collisions.
+pub mod collision;
+
+/// Particle emitters.
+pub mod emitter;
+
+/// Particle events and sub-emitters.
+pub mod events;
+
+/// Effect definitions.
+pub mod effect;
+
+/// Ambient spawning.
+pub mod schedule;
+
+/// Simulation trait.
+pub mod simulation;
+
 /// Confetti Simulation.
 pub mod simulation_confetti;
 
@@ -28,5 +79,8 @@ pub mod simulation_fireworks;
 /// Shooting Star Simulation.
 pub mod simulation_shooting_star;
 
+/// Formation Simulation.
+pub mod simulation_formation;
+
 /// Constants.
 pub mod consts;
@@ -0,0 +1,118 @@
+use crate::color::lerp_oklab;
+use ratatui::style::Color;
+use std::time::Duration;
+
+/// When a particle was spawned and how long it lives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lifetime {
+    /// Simulation time at which the particle was spawned.
+    pub spawned_at: Duration,
+    /// How long the particle lives, or `None` if it only dies by leaving the
+    /// screen.
+    pub lifespan: Option<Duration>,
+}
+
+impl Lifetime {
+    /// A particle spawned at `spawned_at` that burns out after `lifespan`.
+    pub fn new(spawned_at: Duration, lifespan: Duration) -> Self {
+        Self {
+            spawned_at,
+            lifespan: Some(lifespan),
+        }
+    }
+
+    /// A particle spawned at `spawned_at` that never burns out.
+    pub fn immortal(spawned_at: Duration) -> Self {
+        Self {
+            spawned_at,
+            lifespan: None,
+        }
+    }
+
+    /// Returns how long the particle has lived at simulation time `now`.
+    pub fn age(&self, now: Duration) -> Duration {
+        now.saturating_sub(self.spawned_at)
+    }
+
+    /// Returns the fraction of its life the particle has lived at `now`, from
+    /// `0.0` at spawn to `1.0` at expiry. Immortal particles stay at `0.0`.
+    pub fn progress(&self, now: Duration) -> f32 {
+        match self.lifespan {
+            Some(lifespan) if !lifespan.is_zero() => {
+                (self.age(now).as_secs_f32() / lifespan.as_secs_f32()).min(1.0)
+            }
+            Some(_) => 1.0,
+            None => 0.0,
+        }
+    }
+
+    /// Returns whether the particle has outlived its lifespan at `now`.
+    pub fn is_expired(&self, now: Duration) -> bool {
+        self.lifespan
+            .is_some_and(|lifespan| self.age(now) >= lifespan)
+    }
+
+    /// Returns how far the particle has faded at `now`, from `0.0` while it
+    /// burns at full brightness to `1.0` at expiry. Fading starts halfway
+    /// through its life.
+    pub fn fade(&self, now: Duration) -> f32 {
+        ((self.progress(now) - 0.5) * 2.0).clamp(0.0, 1.0)
+    }
+}
+
+/// Picks the entry of `ramp` matching `progress` through a particle's life,
+/// e.g. `['*', '+', '·']` for a spark that dwindles as it burns out.
+pub fn ramp<T: Copy>(ramp: &[T], progress: f32) -> Option<T> {
+    let index = (progress.clamp(0.0, 1.0) * ramp.len() as f32) as usize;
+    ramp.get(index.min(ramp.len().saturating_sub(1))).copied()
+}
+
+/// Returns the character and colour at simulation time `now` of a particle
+/// drawn as `char` in `color`: a mortal particle dwindles through `chars`
+/// and fades toward `background` as it burns out, an immortal one keeps its
+/// look.
+pub fn appearance(
+    life: &Lifetime,
+    now: Duration,
+    (char, color): (char, Color),
+    chars: &[char],
+    background: Color,
+) -> (char, Color) {
+    if life.lifespan.is_none() {
+        return (char, color);
+    }
+    (
+        ramp(chars, life.progress(now)).unwrap_or(char),
+        lerp_oklab(color, background, life.fade(now)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lifetime_progresses_and_expires() {
+        let life = Lifetime::new(Duration::from_secs(1), Duration::from_secs(2));
+        assert_eq!(life.progress(Duration::ZERO), 0.0);
+        assert_eq!(life.progress(Duration::from_secs(2)), 0.5);
+        assert_eq!(life.fade(Duration::from_secs(2)), 0.0);
+        assert_eq!(life.fade(Duration::from_millis(2500)), 0.5);
+        assert!(!life.is_expired(Duration::from_millis(2999)));
+        assert!(life.is_expired(Duration::from_secs(3)));
+
+        let immortal = Lifetime::immortal(Duration::ZERO);
+        assert_eq!(immortal.progress(Duration::from_secs(1000)), 0.0);
+        assert!(!immortal.is_expired(Duration::from_secs(1000)));
+    }
+
+    #[test]
+    fn ramp_walks_through_every_entry() {
+        let chars = ['*', '+', '·'];
+        assert_eq!(ramp(&chars, 0.0), Some('*'));
+        assert_eq!(ramp(&chars, 0.5), Some('+'));
+        assert_eq!(ramp(&chars, 0.9), Some('·'));
+        assert_eq!(ramp(&chars, 1.0), Some('·'));
+        assert_eq!(ramp::<char>(&[], 0.5), None);
+    }
+}
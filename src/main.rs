@@ -1,11 +1,24 @@
 mod consts;
 use crate::consts::TICK_RATE_IN_MILI;
 use clap::Parser;
-use confetty_rs::app::{App, AppResult};
+use confetty_rs::app::{App, AppResult, Mode, Settings};
+use confetty_rs::collision::Collisions;
+use confetty_rs::effect::{EffectDefinition, SimulationStateEffect};
 use confetty_rs::event::{Event, EventHandler};
-use confetty_rs::handler::handle_key_events;
+use confetty_rs::font::Font;
+use confetty_rs::glyphs::GlyphSet;
+use confetty_rs::handler::{handle_key_events, handle_mouse_events};
+use confetty_rs::image;
+use confetty_rs::keymap::KeyMap;
+use confetty_rs::palette::{ColorSupport, PaletteConfig};
+use confetty_rs::projectile::Integrator;
+use confetty_rs::render::{RenderOptions, Resolution};
+use confetty_rs::schedule::Schedule;
+use confetty_rs::simulation_formation::SimulationStateFormation;
 use confetty_rs::tui::Tui;
+use nalgebra::Vector2;
 use ratatui::backend::CrosstermBackend;
+use ratatui::layout::Rect;
 use ratatui::Terminal;
 use std::io;
 
@@ -14,29 +27,240 @@ use std::io;
 struct Args {
     #[arg(short, long)]
     name: Option<String>,
+
+    /// Effect definition file, or the name of a built-in effect: confetti, fireworks, stars or crackle
+    #[arg(long, value_name = "PATH", conflicts_with = "name")]
+    effect: Option<String>,
+
+    /// Text for the particles to assemble into, flying in like the simulation given by --name
+    #[arg(long, conflicts_with = "effect")]
+    text: Option<String>,
+
+    /// FIGlet font (.flf) to draw --text with instead of the built-in one
+    #[arg(long, value_name = "PATH", requires = "text")]
+    font: Option<String>,
+
+    /// PNG or PPM image, or ASCII-art text file, to shatter into particles
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["effect", "text"])]
+    image: Option<String>,
+
+    /// Fly the pieces of --image in like the simulation given by --name and assemble them instead
+    #[arg(long, requires = "image")]
+    assemble: bool,
+
+    /// Milliseconds between ticks
+    #[arg(long, default_value_t = TICK_RATE_IN_MILI)]
+    tick_rate: u64,
+
+    /// Physics integrator: euler, semi-implicit-euler, verlet or rk4
+    #[arg(long, default_value_t = Integrator::default())]
+    integrator: Integrator,
+
+    /// Wind velocity as `x,y` in cells per second, felt through drag
+    #[arg(long, value_parser = parse_vector, allow_hyphen_values = true)]
+    wind: Option<Vector2<f32>>,
+
+    /// Seed for the random number generator, for reproducible runs
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Particle resolution: cell, half-block or braille
+    #[arg(long, default_value_t = Resolution::default())]
+    resolution: Resolution,
+
+    /// Leave a fading trail behind every particle, losing this much intensity per second
+    #[arg(long, value_name = "DECAY")]
+    trail: Option<f32>,
+
+    /// Spawn bursts on their own, this many per second on average
+    #[arg(long, value_name = "RATE")]
+    auto_rate: Option<f32>,
+
+    /// Randomly stretch or shrink each interval between automatic bursts by up to this fraction
+    #[arg(
+        long,
+        value_name = "FRACTION",
+        default_value_t = 0.0,
+        requires = "auto_rate"
+    )]
+    auto_jitter: f32,
+
+    /// Make particles bounce off one another
+    #[arg(long)]
+    collisions: bool,
+
+    /// Share of their approach speed colliding particles part with, from 0 (inelastic) to 1 (elastic)
+    #[arg(
+        long,
+        value_parser = parse_restitution,
+        default_value_t = Collisions::default().restitution,
+        requires = "collisions"
+    )]
+    restitution: f32,
+
+    /// Radius of a particle for collisions, in cells, greater than 0
+    #[arg(
+        long,
+        value_name = "CELLS",
+        value_parser = parse_radius,
+        default_value_t = Collisions::default().radius,
+        requires = "collisions"
+    )]
+    collision_radius: f32,
+
+    /// Keep particles at the same relative position when the terminal is resized
+    #[arg(long)]
+    remap_on_resize: bool,
+
+    /// Let particles finish when switching modes instead of clearing them
+    #[arg(long)]
+    carry_over: bool,
+
+    /// Palette name (default, rainbow, pastel, gold, monochrome, high-contrast), or colours like `#ff0000,#00ff00`
+    #[arg(long)]
+    palette: Option<String>,
+
+    /// Palette file defining palettes and the palette of each simulation
+    #[arg(long, value_name = "PATH")]
+    palette_file: Option<String>,
+
+    /// Colours the terminal supports: truecolor, 256 or 16, detected from COLORTERM and TERM by default
+    #[arg(long)]
+    colors: Option<ColorSupport>,
+
+    /// Glyph set: unicode, ascii, emoji or the characters to use, optionally for one simulation as `MODE=SET`
+    #[arg(long, value_name = "[MODE=]SET", value_parser = parse_glyphs)]
+    glyphs: Vec<(Option<Mode>, GlyphSet)>,
+
+    /// Only draw printable 7-bit characters
+    #[arg(long)]
+    ascii: bool,
+
+    /// Key map file binding actions to keys, e.g. `pause = ["p", "space"]`
+    #[arg(long, value_name = "PATH")]
+    keymap: Option<String>,
 }
 
-fn main() -> AppResult<()> {
-    let args = Args::parse();
+fn parse_vector(s: &str) -> Result<Vector2<f32>, String> {
+    let (x, y) = s
+        .split_once(',')
+        .ok_or_else(|| format!("expected `x,y`, got `{s}`"))?;
+    let parse = |v: &str| v.trim().parse::<f32>().map_err(|e| e.to_string());
+    Ok(Vector2::new(parse(x)?, parse(y)?))
+}
 
-    // Get the value of the state argument, if provided
-    let mut name = args.name.unwrap_or_default();
+fn parse_restitution(s: &str) -> Result<f32, String> {
+    let restitution = s.parse::<f32>().map_err(|e| e.to_string())?;
+    match (0.0..=1.0).contains(&restitution) {
+        true => Ok(restitution),
+        false => Err(format!("expected a number from 0 to 1, got `{s}`")),
+    }
+}
 
-    if name.is_empty() {
-        name = String::from("confetti");
+fn parse_radius(s: &str) -> Result<f32, String> {
+    let radius = s.parse::<f32>().map_err(|e| e.to_string())?;
+    match radius.is_finite() && radius > 0.0 {
+        true => Ok(radius),
+        false => Err(format!(
+            "expected a number of cells greater than 0, got `{s}`"
+        )),
     }
+}
+
+fn parse_glyphs(s: &str) -> Result<(Option<Mode>, GlyphSet), String> {
+    match s.split_once('=') {
+        Some((mode, set)) if mode.parse::<Mode>().is_ok() => Ok((mode.parse().ok(), set.parse()?)),
+        _ => Ok((None, s.parse()?)),
+    }
+}
+
+fn main() -> AppResult<()> {
+    let args = Args::parse();
 
-    // Create an application.
-    let mut app = match name.as_str() {
-        "fireworks" => App::fireworks(),
-        "stars" => App::shooting_star(),
-        _ => App::new(),
+    // Create an application, defaulting to confetti for unknown names.
+    let mode = args
+        .name
+        .as_deref()
+        .unwrap_or_default()
+        .parse::<Mode>()
+        .unwrap_or_default();
+    // Text borrows the palette and glyphs of the simulation it flies in like.
+    let styled_as = (args.text.is_some() || args.image.is_some()).then_some(mode);
+    let mut app = match (args.effect, args.text, args.image) {
+        (Some(effect), _, _) => {
+            let definition = EffectDefinition::resolve(&effect)?;
+            App::with_simulation(Box::new(SimulationStateEffect::new(definition)?))
+        }
+        (None, Some(text), _) => {
+            let font = match args.font {
+                Some(path) => Font::load(path)?,
+                None => Font::default(),
+            };
+            let figure = font.render(&text);
+            App::with_simulation(Box::new(SimulationStateFormation::new(figure, mode)))
+        }
+        (None, None, Some(path)) => {
+            let simulation = SimulationStateFormation::new(image::load(path)?, mode);
+            if args.assemble {
+                App::with_simulation(Box::new(simulation))
+            } else {
+                App::with_simulation(Box::new(simulation.shattering()))
+            }
+        }
+        (None, None, None) => App::with_mode(mode),
     };
+    if let Some(path) = args.keymap {
+        app.keymap = KeyMap::load(path)?;
+    }
+    if let Some(rate) = args.auto_rate {
+        app.schedule = Some(Schedule::new(rate, args.auto_jitter));
+    }
+    if let (Some(seed), Some(schedule)) = (args.seed, &mut app.schedule) {
+        schedule.reseed(seed);
+    }
+    // Figures are spawned right away rather than on the first key press.
+    app.spawn_on_start = styled_as.is_some();
+    app.remap_on_resize = args.remap_on_resize;
+    app.carry_over = args.carry_over;
+    app.settings = Settings {
+        seed: args.seed,
+        integrator: args.integrator,
+        render_options: RenderOptions {
+            resolution: args.resolution,
+            colors: args.colors.unwrap_or_else(ColorSupport::detect),
+            ascii: args.ascii,
+            ..RenderOptions::default()
+        },
+        trail: args.trail,
+        wind: args.wind,
+        collisions: args.collisions.then_some(Collisions {
+            radius: args.collision_radius,
+            restitution: args.restitution,
+        }),
+        palette: None,
+        palettes: match args.palette_file {
+            Some(path) => PaletteConfig::load(path)?,
+            None => PaletteConfig::default(),
+        },
+        glyphs: args.ascii.then_some(GlyphSet::Ascii),
+        glyph_sets: Default::default(),
+    };
+    for (mode, glyphs) in args.glyphs {
+        match mode {
+            Some(mode) => app.settings.glyph_sets.insert(mode, glyphs),
+            None => app.settings.glyphs.replace(glyphs),
+        };
+    }
+    if let Some(palette) = args.palette {
+        app.settings.palette = Some(app.settings.palettes.resolve(&palette)?);
+    }
+    app.settings
+        .apply(app.mode.or(styled_as), app.state.as_mut());
 
     // Initialize the terminal user interface.
     let backend = CrosstermBackend::new(io::stderr());
     let terminal = Terminal::new(backend)?;
-    let events = EventHandler::new(TICK_RATE_IN_MILI);
+    let events = EventHandler::new(args.tick_rate);
     let mut tui = Tui::new(terminal, events);
     tui.init()?;
 
@@ -46,10 +270,12 @@ fn main() -> AppResult<()> {
         tui.draw(&mut app)?;
         // Handle events.
         match tui.events.next()? {
-            Event::Tick => app.tick(),
+            Event::Tick(elapsed) => app.tick(elapsed),
             Event::Key(key_event) => handle_key_events(key_event, &mut app)?,
-            Event::Mouse(_) => {}
-            Event::Resize(_, _) => {}
+            Event::Mouse(mouse_event) => handle_mouse_events(mouse_event, &mut app)?,
+            // Adopt the new size before the next tick, so nothing is
+            // simulated or drawn against stale bounds.
+            Event::Resize(width, height) => app.set_area(Rect::new(0, 0, width, height)),
         }
     }
 
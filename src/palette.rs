@@ -0,0 +1,362 @@
+use crate::app::Mode;
+use crate::color::{parse_hex, to_ansi16, to_ansi256};
+use crate::consts::COLORS;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use ratatui::style::Color;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::{env, error, fs, io};
+
+/// Palettes bundled with the crate, by name.
+pub const BUILTIN_PALETTES: [(&str, &[Color]); 6] = [
+    ("default", &COLORS),
+    (
+        "rainbow",
+        &[
+            Color::Rgb(255, 59, 48),  // #ff3b30
+            Color::Rgb(255, 149, 0),  // #ff9500
+            Color::Rgb(255, 204, 0),  // #ffcc00
+            Color::Rgb(52, 199, 89),  // #34c759
+            Color::Rgb(0, 122, 255),  // #007aff
+            Color::Rgb(175, 82, 222), // #af52de
+        ],
+    ),
+    (
+        "pastel",
+        &[
+            Color::Rgb(255, 179, 186), // #ffb3ba
+            Color::Rgb(255, 223, 186), // #ffdfba
+            Color::Rgb(255, 255, 186), // #ffffba
+            Color::Rgb(186, 255, 201), // #baffc9
+            Color::Rgb(186, 225, 255), // #bae1ff
+            Color::Rgb(224, 187, 228), // #e0bbe4
+        ],
+    ),
+    (
+        "gold",
+        &[
+            Color::Rgb(255, 215, 0),   // #ffd700
+            Color::Rgb(255, 193, 37),  // #ffc125
+            Color::Rgb(240, 230, 140), // #f0e68c
+            Color::Rgb(218, 165, 32),  // #daa520
+            Color::Rgb(255, 248, 220), // #fff8dc
+        ],
+    ),
+    (
+        "monochrome",
+        &[
+            Color::Rgb(255, 255, 255), // #ffffff
+            Color::Rgb(217, 217, 217), // #d9d9d9
+            Color::Rgb(179, 179, 179), // #b3b3b3
+            Color::Rgb(140, 140, 140), // #8c8c8c
+        ],
+    ),
+    (
+        "high-contrast",
+        &[
+            Color::Rgb(255, 255, 255), // #ffffff
+            Color::Rgb(255, 255, 0),   // #ffff00
+            Color::Rgb(0, 255, 255),   // #00ffff
+            Color::Rgb(255, 0, 255),   // #ff00ff
+            Color::Rgb(0, 255, 0),     // #00ff00
+        ],
+    ),
+];
+
+/// The colours particles are drawn from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    colors: Vec<Color>,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::new(COLORS.to_vec())
+    }
+}
+
+impl Palette {
+    pub fn new(colors: Vec<Color>) -> Self {
+        Self { colors }
+    }
+
+    /// Returns the bundled palette called `name`.
+    pub fn builtin(name: &str) -> Option<Self> {
+        BUILTIN_PALETTES
+            .iter()
+            .find(|(builtin, _)| *builtin == name)
+            .map(|(_, colors)| Self::new(colors.to_vec()))
+    }
+
+    pub fn colors(&self) -> &[Color] {
+        &self.colors
+    }
+
+    /// Picks a colour at random.
+    pub fn choose(&self, rng: &mut impl Rng) -> Color {
+        self.colors.choose(rng).copied().unwrap_or(Color::Reset)
+    }
+}
+
+/// Parses the name of a built-in palette, or a comma-separated list of
+/// `#rrggbb` colours.
+impl FromStr for Palette {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(palette) = Self::builtin(s) {
+            return Ok(palette);
+        }
+        if !s.contains('#') {
+            let names: Vec<_> = BUILTIN_PALETTES.iter().map(|(name, _)| *name).collect();
+            return Err(format!(
+                "unknown palette `{s}` (expected {} or a list of #rrggbb colours)",
+                names.join(", ")
+            ));
+        }
+        s.split(',')
+            .map(|color| parse_hex(color).ok_or_else(|| format!("invalid colour `{color}`")))
+            .collect::<Result<_, _>>()
+            .map(Self::new)
+    }
+}
+
+/// Error raised while loading a palette file.
+#[derive(Debug)]
+pub enum PaletteError {
+    /// The palette file could not be read.
+    Io(io::Error),
+    /// The palette file is not valid TOML or does not match the format.
+    Parse(toml::de::Error),
+    /// The palette file parsed but names an unknown palette, colour or
+    /// simulation.
+    Invalid(String),
+}
+
+impl fmt::Display for PaletteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaletteError::Io(e) => write!(f, "cannot read palettes: {e}"),
+            PaletteError::Parse(e) => write!(f, "cannot parse palettes: {e}"),
+            PaletteError::Invalid(reason) => write!(f, "invalid palettes: {reason}"),
+        }
+    }
+}
+
+impl error::Error for PaletteError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            PaletteError::Io(e) => Some(e),
+            PaletteError::Parse(e) => Some(e),
+            PaletteError::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for PaletteError {
+    fn from(e: io::Error) -> Self {
+        PaletteError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for PaletteError {
+    fn from(e: toml::de::Error) -> Self {
+        PaletteError::Parse(e)
+    }
+}
+
+/// A palette file as written.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PaletteFile {
+    #[serde(default)]
+    palettes: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    simulations: BTreeMap<String, String>,
+}
+
+/// User palettes and the palette each built-in simulation uses.
+///
+/// Palette files are written in TOML:
+///
+/// ```toml
+/// [palettes]
+/// sunset = ["#ff5e62", "#ff9966", "#ffd452"]
+///
+/// [simulations]
+/// confetti = "sunset"
+/// fireworks = "gold"
+/// stars = "#ffffff,#bae1ff"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PaletteConfig {
+    /// User palettes, by name.
+    pub palettes: BTreeMap<String, Palette>,
+    /// Palette of each simulation that does not use the default one.
+    pub simulations: BTreeMap<Mode, Palette>,
+}
+
+impl PaletteConfig {
+    /// Parses a palette file from TOML.
+    pub fn from_toml(source: &str) -> Result<Self, PaletteError> {
+        let file: PaletteFile = toml::from_str(source)?;
+        let mut config = Self::default();
+        for (name, colors) in file.palettes {
+            let colors = colors
+                .iter()
+                .map(|color| {
+                    parse_hex(color).ok_or_else(|| {
+                        PaletteError::Invalid(format!("palette `{name}`: invalid colour `{color}`"))
+                    })
+                })
+                .collect::<Result<_, _>>()?;
+            config.palettes.insert(name, Palette::new(colors));
+        }
+        for (simulation, palette) in file.simulations {
+            let mode = simulation.parse().map_err(PaletteError::Invalid)?;
+            let palette = config.resolve(&palette).map_err(PaletteError::Invalid)?;
+            config.simulations.insert(mode, palette);
+        }
+        Ok(config)
+    }
+
+    /// Reads and parses the palette file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PaletteError> {
+        Self::from_toml(&fs::read_to_string(path)?)
+    }
+
+    /// Looks `spec` up as a user palette, then as a built-in palette or a
+    /// list of colours.
+    pub fn resolve(&self, spec: &str) -> Result<Palette, String> {
+        match self.palettes.get(spec) {
+            Some(palette) => Ok(palette.clone()),
+            None => spec.parse(),
+        }
+    }
+}
+
+/// How many colours the terminal can show.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSupport {
+    /// Any RGB colour.
+    #[default]
+    TrueColor,
+    /// The ANSI-256 palette.
+    Ansi256,
+    /// The 16 standard ANSI colours.
+    Ansi16,
+}
+
+impl ColorSupport {
+    /// Works out what the terminal supports from the `COLORTERM` and `TERM`
+    /// environment variables.
+    pub fn detect() -> Self {
+        Self::from_env(
+            env::var("COLORTERM").ok().as_deref(),
+            env::var("TERM").ok().as_deref(),
+        )
+    }
+
+    /// Works out what a terminal with the given `COLORTERM` and `TERM`
+    /// supports, assuming the least when neither says.
+    pub fn from_env(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        match (colorterm, term) {
+            (Some("truecolor" | "24bit"), _) => ColorSupport::TrueColor,
+            (_, Some(term)) if term.ends_with("-direct") => ColorSupport::TrueColor,
+            (_, Some(term)) if term.contains("256color") => ColorSupport::Ansi256,
+            _ => ColorSupport::Ansi16,
+        }
+    }
+
+    /// Returns the closest colour to `color` the terminal can show.
+    pub fn downgrade(self, color: Color) -> Color {
+        match self {
+            ColorSupport::TrueColor => color,
+            ColorSupport::Ansi256 => to_ansi256(color),
+            ColorSupport::Ansi16 => to_ansi16(color),
+        }
+    }
+}
+
+impl fmt::Display for ColorSupport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ColorSupport::TrueColor => "truecolor",
+            ColorSupport::Ansi256 => "256",
+            ColorSupport::Ansi16 => "16",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for ColorSupport {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "truecolor" => Ok(ColorSupport::TrueColor),
+            "256" => Ok(ColorSupport::Ansi256),
+            "16" => Ok(ColorSupport::Ansi16),
+            _ => Err(format!(
+                "unknown colour support `{s}` (expected truecolor, 256 or 16)"
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_names_and_colour_lists() {
+        assert_eq!("default".parse::<Palette>(), Ok(Palette::default()));
+        assert_eq!(
+            "#ff0000, #00ff00".parse::<Palette>(),
+            Ok(Palette::new(vec![
+                Color::Rgb(255, 0, 0),
+                Color::Rgb(0, 255, 0)
+            ]))
+        );
+        assert!("neon".parse::<Palette>().is_err());
+        assert!("#ff0000,#nope".parse::<Palette>().is_err());
+        for (name, _) in BUILTIN_PALETTES {
+            assert!(name.parse::<Palette>().is_ok());
+        }
+    }
+
+    #[test]
+    fn palette_files_define_and_assign_palettes() {
+        let config = PaletteConfig::from_toml(
+            "[palettes]\nsunset = [\"#ff5e62\", \"#ffd452\"]\n\
+             [simulations]\nconfetti = \"sunset\"\nfireworks = \"gold\"",
+        )
+        .unwrap();
+        assert_eq!(config.simulations[&Mode::Confetti].colors().len(), 2);
+        assert_eq!(
+            config.simulations[&Mode::Fireworks],
+            Palette::builtin("gold").unwrap()
+        );
+        assert!(!config.simulations.contains_key(&Mode::Stars));
+
+        assert!(PaletteConfig::from_toml("[simulations]\nrain = \"gold\"").is_err());
+        assert!(PaletteConfig::from_toml("[simulations]\nstars = \"sunset\"").is_err());
+    }
+
+    #[test]
+    fn detects_colour_support_from_the_environment() {
+        let detect = ColorSupport::from_env;
+        assert_eq!(
+            detect(Some("truecolor"), Some("xterm")),
+            ColorSupport::TrueColor
+        );
+        assert_eq!(detect(None, Some("xterm-direct")), ColorSupport::TrueColor);
+        assert_eq!(detect(None, Some("xterm-256color")), ColorSupport::Ansi256);
+        assert_eq!(detect(None, Some("linux")), ColorSupport::Ansi16);
+        assert_eq!(detect(None, None), ColorSupport::Ansi16);
+    }
+}
@@ -0,0 +1,469 @@
+use crate::color::{lerp_rgb, rgb};
+use crate::glyphs::to_ascii;
+use crate::palette::ColorSupport;
+use ratatui::prelude::*;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// How finely particle positions are resolved within a terminal cell.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// One particle per cell, drawn with its own character.
+    #[default]
+    Cell,
+    /// Two vertical dots per cell, drawn with `▀` and `▄`.
+    HalfBlock,
+    /// A 2x4 grid of dots per cell, drawn with Braille patterns.
+    Braille,
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Resolution::Cell => "cell",
+            Resolution::HalfBlock => "half-block",
+            Resolution::Braille => "braille",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Resolution {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cell" => Ok(Resolution::Cell),
+            "half-block" => Ok(Resolution::HalfBlock),
+            "braille" => Ok(Resolution::Braille),
+            _ => Err(format!(
+                "unknown resolution `{s}` (expected cell, half-block or braille)"
+            )),
+        }
+    }
+}
+
+/// How particles are composed with what is already in the [`Buffer`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderOptions {
+    /// Leave cells that already hold a non-blank symbol untouched, so
+    /// particles pass behind existing content.
+    pub behind_content: bool,
+    /// Particle opacity between `0.0` and `1.0`. Below `1.0` the particle
+    /// colour is blended with the background colour of the cell it lands on.
+    pub alpha: f32,
+    /// Colour assumed behind cells whose background is not an RGB colour,
+    /// e.g. the terminal default.
+    pub backdrop: Color,
+    /// How finely particle positions are resolved within a cell.
+    pub resolution: Resolution,
+    /// Colours the terminal can show. Colours are blended in RGB and only
+    /// brought down to this when written.
+    pub colors: ColorSupport,
+    /// Replace every character with a printable 7-bit one when it is
+    /// written, including the dots of sub-cell resolutions.
+    pub ascii: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            behind_content: false,
+            alpha: 1.0,
+            backdrop: Color::Rgb(0, 0, 0),
+            resolution: Resolution::Cell,
+            colors: ColorSupport::TrueColor,
+            ascii: false,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Options for showering particles over an existing user interface: they
+    /// slip behind text and are slightly translucent.
+    pub fn overlay() -> Self {
+        Self {
+            behind_content: true,
+            alpha: 0.85,
+            ..Self::default()
+        }
+    }
+}
+
+/// Dots and colours collected for one cell at sub-cell resolution.
+#[derive(Debug, Default)]
+struct SubCell {
+    /// One bit per dot, in Braille order, or bit 0 for the top and bit 1 for
+    /// the bottom half of a half-block cell.
+    dots: u8,
+    /// Colours of the particles in each half of a half-block cell.
+    halves: [Option<Color>; 2],
+    /// Running sum of the RGB colours of every particle in the cell.
+    sum: [u32; 3],
+    count: u32,
+    /// Colour of the last particle, used when colours are not RGB.
+    last: Option<Color>,
+}
+
+impl SubCell {
+    fn add(&mut self, dot: u8, color: Color) {
+        self.dots |= dot;
+        if let Some((r, g, b)) = rgb(color) {
+            self.sum[0] += u32::from(r);
+            self.sum[1] += u32::from(g);
+            self.sum[2] += u32::from(b);
+            self.count += 1;
+        }
+        self.last = Some(color);
+    }
+
+    /// Returns the average of every RGB colour in the cell.
+    fn color(&self) -> Color {
+        match (self.count, self.last) {
+            (0, last) => last.unwrap_or(Color::Reset),
+            (n, _) => Color::Rgb(
+                (self.sum[0] / n) as u8,
+                (self.sum[1] / n) as u8,
+                (self.sum[2] / n) as u8,
+            ),
+        }
+    }
+}
+
+// Bit of each dot of a Braille pattern, indexed by [row][column].
+const BRAILLE_DOTS: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// Draws particles into a [`Buffer`], translating simulation coordinates
+/// into cells of `area` and clipping everything that falls outside it.
+///
+/// Simulations work in coordinates relative to the top-left corner of the
+/// area they were given, so `(0.0, 0.0)` is always the first cell of `area`,
+/// wherever that area sits in the buffer.
+///
+/// At sub-cell [`Resolution`]s particles are collected while drawing and
+/// written to the buffer when the painter is dropped.
+#[derive(Debug)]
+pub struct Painter<'a> {
+    buf: &'a mut Buffer,
+    area: Rect,
+    clip: Rect,
+    options: RenderOptions,
+    /// Cells of `clip` that held content before any particle was drawn,
+    /// including the second half of double-width characters.
+    occupied: Vec<bool>,
+    /// Particles collected at sub-cell resolution, keyed by buffer cell.
+    sub_cells: HashMap<(u16, u16), SubCell>,
+}
+
+impl<'a> Painter<'a> {
+    /// Constructs a [`Painter`] that draws into `area` of `buf`.
+    pub fn new(area: Rect, buf: &'a mut Buffer) -> Self {
+        Self::with_options(area, buf, RenderOptions::default())
+    }
+
+    /// Constructs a [`Painter`] that composes particles according to
+    /// `options`.
+    pub fn with_options(area: Rect, buf: &'a mut Buffer, options: RenderOptions) -> Self {
+        let clip = area.intersection(buf.area);
+        let occupied = if options.behind_content {
+            clip.rows()
+                .flat_map(|row| row.columns())
+                .map(|cell| {
+                    let covered =
+                        cell.x > buf.area.x && buf.get(cell.x - 1, cell.y).symbol().width() > 1;
+                    covered || buf.get(cell.x, cell.y).symbol() != " "
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+        Self {
+            buf,
+            area,
+            clip,
+            options,
+            occupied,
+            sub_cells: HashMap::new(),
+        }
+    }
+
+    /// Returns the area particle coordinates are relative to.
+    pub fn area(&self) -> Rect {
+        self.area
+    }
+
+    /// Returns the cell of the buffer that `(x, y)` falls into, or `None` if
+    /// that cell is outside the drawable area.
+    pub fn cell_position(&self, x: f32, y: f32) -> Option<(u16, u16)> {
+        if !x.is_finite() || !y.is_finite() {
+            return None;
+        }
+        let column = i64::from(self.area.x).saturating_add(x.floor() as i64);
+        let row = i64::from(self.area.y).saturating_add(y.floor() as i64);
+
+        let columns = i64::from(self.clip.left())..i64::from(self.clip.right());
+        let rows = i64::from(self.clip.top())..i64::from(self.clip.bottom());
+        if columns.contains(&column) && rows.contains(&row) {
+            Some((column as u16, row as u16))
+        } else {
+            None
+        }
+    }
+
+    /// Draws `char` in `color` at `(x, y)`, if that is inside the area.
+    ///
+    /// At sub-cell resolutions `char` is replaced by a dot at the exact
+    /// position of the particle.
+    pub fn put(&mut self, x: f32, y: f32, char: char, color: Color) {
+        let Some((column, row)) = self.cell_position(x, y) else {
+            return;
+        };
+
+        // Position of the particle within its cell, in [0, 1).
+        let (fx, fy) = (x - x.floor(), y - y.floor());
+        match self.options.resolution {
+            Resolution::Cell => self.write(column, row, char, color),
+            Resolution::HalfBlock => {
+                let half = usize::from(fy >= 0.5);
+                let sub_cell = self.sub_cells.entry((column, row)).or_default();
+                sub_cell.add(1 << half, color);
+                sub_cell.halves[half] = Some(color);
+            }
+            Resolution::Braille => {
+                let dot_column = ((fx * 2.0) as usize).min(1);
+                let dot_row = ((fy * 4.0) as usize).min(3);
+                self.sub_cells
+                    .entry((column, row))
+                    .or_default()
+                    .add(BRAILLE_DOTS[dot_row][dot_column], color);
+            }
+        }
+    }
+
+    /// Writes every particle collected at sub-cell resolution.
+    fn flush(&mut self) {
+        for ((column, row), sub_cell) in std::mem::take(&mut self.sub_cells) {
+            match self.options.resolution {
+                Resolution::Cell => {}
+                Resolution::HalfBlock => match sub_cell.halves {
+                    [Some(top), Some(bottom)] => {
+                        self.write(column, row, '▀', top);
+                        if !self.is_occupied(column, row) {
+                            let bottom = self.compose(column, row, bottom);
+                            self.buf.get_mut(column, row).bg = bottom;
+                        }
+                    }
+                    [Some(top), None] => self.write(column, row, '▀', top),
+                    [None, Some(bottom)] => self.write(column, row, '▄', bottom),
+                    [None, None] => {}
+                },
+                Resolution::Braille => {
+                    let char = char::from_u32(0x2800 + u32::from(sub_cell.dots)).unwrap_or('⠀');
+                    self.write(column, row, char, sub_cell.color());
+                }
+            }
+        }
+    }
+
+    /// Writes a single cell, honouring the composition options.
+    ///
+    /// Double-width characters also take the cell to their right, and fall
+    /// back to an ASCII stand-in where that cell is outside the area.
+    fn write(&mut self, column: u16, row: u16, char: char, color: Color) {
+        let mut char = if self.options.ascii {
+            to_ascii(char)
+        } else {
+            char
+        };
+        let mut width = match char.width() {
+            Some(0) | None => return,
+            Some(width) => width as u16,
+        };
+        if column + width > self.clip.right() {
+            char = to_ascii(char);
+            width = 1;
+        }
+        if (column..column + width).any(|column| self.is_occupied(column, row)) {
+            return;
+        }
+
+        // Don't leave the first half of a double-width character behind.
+        if column > self.clip.x {
+            let left = self.buf.get_mut(column - 1, row);
+            if left.symbol().width() > 1 {
+                left.set_char(' ');
+            }
+        }
+        let color = self.compose(column, row, color);
+        let cell = self.buf.get_mut(column, row);
+        cell.set_char(char);
+        cell.fg = color;
+        for column in column + 1..column + width {
+            self.buf.get_mut(column, row).set_char(' ');
+        }
+    }
+
+    /// Blends `color` with the background of the cell when translucent, and
+    /// fits the result to the colours the terminal supports.
+    fn compose(&self, column: u16, row: u16, color: Color) -> Color {
+        let color = if self.options.alpha >= 1.0 {
+            color
+        } else {
+            let background = match self.buf.get(column, row).bg {
+                bg @ Color::Rgb(..) => bg,
+                _ => self.options.backdrop,
+            };
+            lerp_rgb(background, color, self.options.alpha)
+        };
+        self.options.colors.downgrade(color)
+    }
+
+    fn is_occupied(&self, column: u16, row: u16) -> bool {
+        let index = (row - self.clip.y) as usize * self.clip.width as usize
+            + (column - self.clip.x) as usize;
+        self.occupied.get(index).copied().unwrap_or(false)
+    }
+}
+
+impl Drop for Painter<'_> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlay_passes_behind_existing_content() {
+        let area = Rect::new(0, 0, 4, 1);
+        let mut buf = Buffer::empty(area);
+        buf.set_string(1, 0, "ok", Style::default());
+
+        let mut painter = Painter::with_options(area, &mut buf, RenderOptions::overlay());
+        for x in 0..4 {
+            painter.put(x as f32, 0.0, '*', Color::Rgb(255, 255, 255));
+        }
+        drop(painter);
+
+        let symbols: String = buf.content.iter().map(|cell| cell.symbol()).collect();
+        assert_eq!(symbols, "*ok*");
+    }
+
+    #[test]
+    fn overlay_keeps_background_and_blends_against_it() {
+        let area = Rect::new(0, 0, 2, 1);
+        let mut buf = Buffer::empty(area);
+        buf.get_mut(0, 0).set_bg(Color::Rgb(0, 0, 200));
+
+        let options = RenderOptions {
+            alpha: 0.5,
+            ..RenderOptions::overlay()
+        };
+        let mut painter = Painter::with_options(area, &mut buf, options);
+        painter.put(0.0, 0.0, '*', Color::Rgb(200, 0, 0));
+        painter.put(1.0, 0.0, '*', Color::Rgb(200, 0, 0));
+        drop(painter);
+
+        assert_eq!(buf.get(0, 0).bg, Color::Rgb(0, 0, 200));
+        assert_eq!(buf.get(0, 0).fg, Color::Rgb(100, 0, 100));
+        // The default background is not RGB, so the backdrop is used.
+        assert_eq!(buf.get(1, 0).fg, Color::Rgb(100, 0, 0));
+    }
+
+    #[test]
+    fn braille_merges_dots_and_colours_within_a_cell() {
+        let area = Rect::new(0, 0, 2, 1);
+        let mut buf = Buffer::empty(area);
+        let options = RenderOptions {
+            resolution: Resolution::Braille,
+            ..RenderOptions::default()
+        };
+
+        let mut painter = Painter::with_options(area, &mut buf, options);
+        painter.put(0.1, 0.1, '*', Color::Rgb(200, 0, 0));
+        painter.put(0.6, 0.9, '*', Color::Rgb(0, 0, 100));
+        painter.put(1.2, 0.3, '*', Color::Rgb(10, 20, 30));
+        drop(painter);
+
+        assert_eq!(buf.get(0, 0).symbol(), "⢁");
+        assert_eq!(buf.get(0, 0).fg, Color::Rgb(100, 0, 50));
+        assert_eq!(buf.get(1, 0).symbol(), "⠂");
+    }
+
+    #[test]
+    fn half_blocks_stack_two_particles_per_cell() {
+        let area = Rect::new(0, 0, 3, 1);
+        let mut buf = Buffer::empty(area);
+        let options = RenderOptions {
+            resolution: Resolution::HalfBlock,
+            ..RenderOptions::default()
+        };
+
+        let mut painter = Painter::with_options(area, &mut buf, options);
+        painter.put(0.5, 0.2, '*', Color::Red);
+        painter.put(1.5, 0.7, '*', Color::Green);
+        painter.put(2.5, 0.2, '*', Color::Red);
+        painter.put(2.5, 0.7, '*', Color::Blue);
+        drop(painter);
+
+        let symbols: String = buf.content.iter().map(|cell| cell.symbol()).collect();
+        assert_eq!(symbols, "▀▄▀");
+        assert_eq!(buf.get(1, 0).fg, Color::Green);
+        assert_eq!(
+            (buf.get(2, 0).fg, buf.get(2, 0).bg),
+            (Color::Red, Color::Blue)
+        );
+    }
+
+    #[test]
+    fn double_width_glyphs_take_two_cells() {
+        let area = Rect::new(0, 0, 5, 1);
+        let mut buf = Buffer::empty(area);
+
+        let mut painter = Painter::new(area, &mut buf);
+        painter.put(0.5, 0.5, '🎉', Color::Red);
+        painter.put(2.5, 0.5, '🎉', Color::Red);
+        painter.put(3.5, 0.5, '*', Color::Blue);
+        painter.put(4.5, 0.5, '🎉', Color::Red);
+        drop(painter);
+
+        let symbols: Vec<_> = buf.content.iter().map(|cell| cell.symbol()).collect();
+        assert_eq!(symbols, ["🎉", " ", " ", "*", "*"]);
+    }
+
+    #[test]
+    fn ascii_replaces_every_glyph() {
+        let area = Rect::new(0, 0, 3, 1);
+        let mut buf = Buffer::empty(area);
+        let options = RenderOptions {
+            resolution: Resolution::Braille,
+            ascii: true,
+            ..RenderOptions::default()
+        };
+
+        let mut painter = Painter::with_options(area, &mut buf, options);
+        painter.put(0.2, 0.1, '*', Color::Red);
+        painter.put(0.7, 0.9, '*', Color::Red);
+        painter.put(1.2, 0.1, '*', Color::Red);
+        drop(painter);
+
+        let symbols: String = buf.content.iter().map(|cell| cell.symbol()).collect();
+        assert_eq!(symbols, ":. ");
+    }
+
+    #[test]
+    fn default_options_draw_over_content() {
+        let area = Rect::new(0, 0, 2, 1);
+        let mut buf = Buffer::empty(area);
+        buf.set_string(0, 0, "ab", Style::default());
+
+        Painter::new(area, &mut buf).put(1.0, 0.0, '*', Color::Red);
+
+        assert_eq!(buf.get(1, 0).symbol(), "*");
+        assert_eq!(buf.get(1, 0).fg, Color::Red);
+    }
+}
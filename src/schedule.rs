@@ -0,0 +1,115 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::Duration;
+
+/// When to spawn bursts without user input, for running unattended.
+///
+/// Bursts arrive `rate` times per second on average. Each interval is
+/// stretched or shrunk at random by up to `jitter` of its length, so a rate
+/// of `0.8` with a jitter of `0.6` spawns every 0.5 to 2 seconds and a
+/// jitter of `0.0` gives a steady stream.
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    rate: f32,
+    jitter: f32,
+    /// Time left until the next burst.
+    until_next: Duration,
+    rng: StdRng,
+}
+
+impl Schedule {
+    /// Constructs a [`Schedule`] whose first burst is due immediately.
+    pub fn new(rate: f32, jitter: f32) -> Self {
+        Self::with_seed(rate, jitter, rand::random())
+    }
+
+    /// Constructs a [`Schedule`] whose intervals are drawn from `seed`.
+    pub fn with_seed(rate: f32, jitter: f32, seed: u64) -> Self {
+        Self {
+            rate: rate.max(0.0),
+            jitter: jitter.clamp(0.0, 1.0),
+            until_next: Duration::ZERO,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Restarts the interval sequence from `seed`.
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    pub fn rate(&self) -> f32 {
+        self.rate
+    }
+
+    pub fn jitter(&self) -> f32 {
+        self.jitter
+    }
+
+    /// Returns how many bursts are due after `dt` has passed.
+    pub fn advance(&mut self, dt: Duration) -> usize {
+        if self.rate <= 0.0 {
+            return 0;
+        }
+        let mut due = 0;
+        let mut dt = dt;
+        while dt >= self.until_next {
+            dt -= self.until_next;
+            self.until_next = self.interval();
+            due += 1;
+        }
+        self.until_next -= dt;
+        due
+    }
+
+    fn interval(&mut self) -> Duration {
+        let mean = 1.0 / self.rate;
+        let stretch = 1.0 + self.jitter * (self.rng.gen::<f32>() * 2.0 - 1.0);
+        // A zero interval would make `advance` loop forever, and rates too
+        // small for `f32` give intervals too long for a `Duration`.
+        Duration::try_from_secs_f32(mean * stretch)
+            .unwrap_or(Duration::MAX)
+            .max(Duration::from_millis(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steady_rate_spawns_at_regular_intervals() {
+        let mut schedule = Schedule::with_seed(4.0, 0.0, 1);
+        assert_eq!(schedule.advance(Duration::ZERO), 1);
+        assert_eq!(schedule.advance(Duration::from_millis(249)), 0);
+        assert_eq!(schedule.advance(Duration::from_millis(1)), 1);
+        assert_eq!(schedule.advance(Duration::from_secs(1)), 4);
+    }
+
+    #[test]
+    fn jitter_keeps_intervals_within_bounds() {
+        let mut schedule = Schedule::with_seed(0.8, 0.6, 1);
+        schedule.advance(Duration::ZERO);
+        for _ in 0..50 {
+            let interval = schedule.until_next;
+            assert!(
+                interval >= Duration::from_millis(499) && interval <= Duration::from_millis(2001),
+                "interval = {interval:?}"
+            );
+            assert_eq!(schedule.advance(interval), 1);
+        }
+    }
+
+    #[test]
+    fn tiny_rate_waits_instead_of_overflowing() {
+        let mut schedule = Schedule::with_seed(1e-40, 0.5, 1);
+        assert_eq!(schedule.advance(Duration::ZERO), 1);
+        assert_eq!(schedule.advance(Duration::from_secs(3600)), 0);
+    }
+
+    #[test]
+    fn zero_rate_never_spawns() {
+        let mut schedule = Schedule::with_seed(0.0, 0.5, 1);
+        assert_eq!(schedule.advance(Duration::from_secs(60)), 0);
+    }
+}
@@ -0,0 +1,293 @@
+use crate::collision::Collisions;
+use crate::forces::ForceField;
+use crate::glyphs::{GlyphSet, Glyphs};
+use crate::palette::Palette;
+use crate::projectile::{Integrator, Projectile};
+use crate::render::{Painter, RenderOptions};
+use crate::trail::TrailBuffer;
+use nalgebra::{Point2, Vector2};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use ratatui::prelude::*;
+use std::fmt::Debug;
+use std::time::Duration;
+
+/// A particle effect that can be driven by [`App`](crate::app::App).
+///
+/// The built-in confetti, fireworks and shooting star effects run as
+/// [`SimulationStateEffect`](crate::effect::SimulationStateEffect)s, and
+/// downstream crates can implement this trait to ship their own effects.
+pub trait Simulation: Debug {
+    /// Advances every particle by `dt` and applies its consequences inside
+    /// `area`: culling, explosions and boundary collisions.
+    fn tick(&mut self, area: Rect, dt: Duration);
+
+    /// Spawns a new burst of particles inside `area`.
+    ///
+    /// Returns the number of particles that were added.
+    fn spawn_particles(&mut self, area: Rect) -> usize;
+
+    /// Spawns a burst aimed at `target`, a position inside `area` relative
+    /// to its top-left corner, e.g. where the mouse was clicked.
+    ///
+    /// Returns the number of particles that were added. Simulations without
+    /// a notion of aim spawn their usual burst.
+    fn spawn_at(&mut self, area: Rect, _target: Point2<f32>) -> usize {
+        self.spawn_particles(area)
+    }
+
+    /// Spawns a steady stream of particles at `target` for `dt`, e.g. while
+    /// the mouse is dragged.
+    ///
+    /// Returns the number of particles that were added.
+    fn stream_at(&mut self, _area: Rect, _target: Point2<f32>, _dt: Duration) -> usize {
+        0
+    }
+
+    /// Scales how many particles bursts and streams spawn, `1.0` being the
+    /// default.
+    fn set_intensity(&mut self, _intensity: f32) {}
+
+    /// Replaces the colours new particles are drawn from.
+    fn set_palette(&mut self, _palette: Palette) {}
+
+    /// Draws new particles, and any trail, with the characters of `glyphs`.
+    fn set_glyphs(&mut self, _glyphs: &GlyphSet) {}
+
+    /// Draws the particles into `buf`.
+    ///
+    /// Rendering never changes the simulation, so frames can be skipped or
+    /// repeated without affecting the outcome.
+    fn render(&self, area: Rect, buf: &mut Buffer);
+
+    /// Adapts the simulation to its area changing from `from` to `to`.
+    ///
+    /// Emitters are anchored to fractions of the area and follow it on their
+    /// own. With `remap`, live particles also keep their position relative to
+    /// the area; otherwise they stay put and are culled if they end up
+    /// outside it.
+    fn resize(&mut self, _from: Rect, _to: Rect, _remap: bool) {}
+
+    /// Returns the number of live particles.
+    fn particle_count(&self) -> usize;
+
+    /// Removes every particle.
+    fn reset(&mut self);
+
+    /// Restarts the random number generator from `seed`, so that the same
+    /// sequence of ticks and spawns always produces the same frames.
+    fn reseed(&mut self, _seed: u64) {}
+
+    /// Makes particles bounce off one another, or pass through each other
+    /// with `None`.
+    fn set_collisions(&mut self, _collisions: Option<Collisions>) {}
+
+    /// Selects the integrator used to advance particles.
+    ///
+    /// Simulations that do not use [`Projectile`]
+    /// can ignore this.
+    fn set_integrator(&mut self, _integrator: Integrator) {}
+
+    /// Changes how particles are composed with what is already in the
+    /// buffer, e.g. [`RenderOptions::overlay`] to draw over another UI.
+    fn set_render_options(&mut self, _options: RenderOptions) {}
+
+    /// Enables an afterglow behind every particle that loses `decay` of its
+    /// intensity per second, or disables it with `None`.
+    fn set_trail(&mut self, _decay: Option<f32>) {}
+
+    /// Returns the forces applied to every particle each tick, if the
+    /// simulation supports them.
+    fn force_field_mut(&mut self) -> Option<&mut ForceField> {
+        None
+    }
+}
+
+/// Maps `pos`, relative to an area of the size of `from`, to the same
+/// fraction of `to`.
+pub fn remap(pos: Point2<f32>, from: Rect, to: Rect) -> Point2<f32> {
+    let scale = |v: f32, from: u16, to: u16| match from {
+        0 => v,
+        _ => v * f32::from(to) / f32::from(from),
+    };
+    Point2::new(
+        scale(pos.x, from.width, to.width),
+        scale(pos.y, from.height, to.height),
+    )
+}
+
+/// Implements [`Widget`] for a reference to a simulation, so it can be
+/// drawn without boxing it first.
+macro_rules! simulation_widget {
+    ($simulation:ty) => {
+        impl ratatui::widgets::Widget for &$simulation {
+            fn render(self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+                $crate::simulation::Simulation::render(self, area, buf);
+            }
+        }
+    };
+}
+
+pub(crate) use simulation_widget;
+
+/// A particle a [`ParticleSystem`] can move, bounce and leave a trail
+/// behind.
+pub trait Body {
+    fn physics(&self) -> &Projectile;
+    fn physics_mut(&mut self) -> &mut Projectile;
+    /// Colour of the trail the particle leaves.
+    fn color(&self) -> Color;
+}
+
+/// The particles of a simulation, and what moves and draws them the same
+/// way whatever the simulation: forces, collisions, randomness, time and
+/// the afterglow.
+#[derive(Debug)]
+pub struct ParticleSystem<P> {
+    pub particles: Vec<P>,
+    pub integrator: Integrator,
+    pub forces: ForceField,
+    /// How particles bounce off one another, if they do.
+    pub collisions: Option<Collisions>,
+    pub rng: StdRng,
+    /// Colours new particles are drawn from.
+    pub palette: Palette,
+    /// Characters new particles are drawn with.
+    pub glyphs: Glyphs,
+    pub render_options: RenderOptions,
+    /// Simulation time, advanced by every tick.
+    pub clock: Duration,
+    /// Afterglow left behind by particles, if enabled.
+    pub trail: Option<TrailBuffer>,
+}
+
+impl<P: Body> ParticleSystem<P> {
+    /// Constructs a system without particles whose randomness, including
+    /// that of `forces`, is drawn from `seed`.
+    pub fn with_seed(seed: u64, forces: ForceField, glyphs: Glyphs) -> Self {
+        Self {
+            particles: vec![],
+            integrator: Integrator::default(),
+            forces: forces.with_seed(seed as u32),
+            collisions: None,
+            rng: StdRng::seed_from_u64(seed),
+            palette: Palette::default(),
+            glyphs,
+            render_options: RenderOptions::default(),
+            clock: Duration::ZERO,
+            trail: None,
+        }
+    }
+
+    /// Returns the physics of a new particle at `pos`, advanced with the
+    /// integrator of the system.
+    pub fn projectile(
+        &self,
+        pos: Point2<f32>,
+        vel: Vector2<f32>,
+        gravity: Vector2<f32>,
+    ) -> Projectile {
+        Projectile::new(pos, vel, gravity).with_integrator(self.integrator)
+    }
+
+    /// Advances every particle by `dt` under its gravity and the forces.
+    pub fn tick(&mut self, area: Rect, dt: Duration) {
+        self.tick_with(area, dt, |particle, forces, _| {
+            let gravity = particle.physics().acceleration();
+            particle
+                .physics_mut()
+                .update_with(dt, |pos, vel| gravity + forces.acceleration(pos, vel));
+        });
+    }
+
+    /// Advances the system by `dt`, moving every particle with `motion`.
+    ///
+    /// Fades the afterglow, leaves a trail behind every particle and bounces
+    /// them off one another before advancing the forces and the clock.
+    pub fn tick_with(
+        &mut self,
+        area: Rect,
+        dt: Duration,
+        mut motion: impl FnMut(&mut P, &ForceField, &mut StdRng),
+    ) {
+        if let Some(trail) = &mut self.trail {
+            trail.resize(area);
+            trail.decay(dt);
+        }
+        for particle in &mut self.particles {
+            let before = particle.physics().position();
+            motion(particle, &self.forces, &mut self.rng);
+            if let Some(trail) = &mut self.trail {
+                trail.deposit(before, particle.physics().position(), particle.color());
+            }
+        }
+        if let Some(collisions) = &self.collisions {
+            collisions.resolve(area, &mut self.particles, P::physics_mut);
+        }
+        self.forces.advance(dt);
+        self.clock += dt;
+    }
+
+    /// Removes the particles at `i`, which must be in ascending order.
+    pub fn remove_indices_from_particles(&mut self, i: Vec<usize>) {
+        for &index in i.iter().rev() {
+            self.particles.swap_remove(index);
+        }
+    }
+
+    /// Draws the afterglow into `buf`, and returns the painter to draw the
+    /// particles over it with.
+    pub fn painter<'a>(&self, area: Rect, buf: &'a mut Buffer) -> Painter<'a> {
+        let mut painter = Painter::with_options(area, buf, self.render_options);
+        if let Some(trail) = &self.trail {
+            trail.render(&mut painter, self.render_options.backdrop);
+        }
+        painter
+    }
+
+    /// Moves every particle to the same fraction of `to` as it was of
+    /// `from`.
+    pub fn remap(&mut self, from: Rect, to: Rect) {
+        for particle in &mut self.particles {
+            let pos = remap(particle.physics().position(), from, to);
+            particle.physics_mut().set_position(pos);
+        }
+    }
+
+    /// Removes every particle and the afterglow they left.
+    pub fn reset(&mut self) {
+        self.particles.clear();
+        if let Some(trail) = &mut self.trail {
+            trail.clear();
+        }
+    }
+
+    /// Restarts the random number generator and the forces from `seed`.
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+        self.forces.set_seed(seed as u32);
+    }
+
+    /// Advances live and new particles with `integrator`.
+    pub fn set_integrator(&mut self, integrator: Integrator) {
+        self.integrator = integrator;
+        for particle in &mut self.particles {
+            particle.physics_mut().set_integrator(integrator);
+        }
+    }
+
+    /// Draws new particles and the afterglow with `glyphs`.
+    pub fn set_glyphs(&mut self, glyphs: Glyphs) {
+        self.glyphs = glyphs;
+        if let Some(trail) = self.trail.take() {
+            self.trail = Some(trail.with_ramp(self.glyphs.trail.clone()));
+        }
+    }
+
+    /// Enables an afterglow that loses `decay` of its intensity per second,
+    /// or disables it with `None`.
+    pub fn set_trail(&mut self, decay: Option<f32>) {
+        self.trail =
+            decay.map(|decay| TrailBuffer::new(decay).with_ramp(self.glyphs.trail.clone()));
+    }
+}
@@ -0,0 +1,269 @@
+use crate::app::Mode;
+use crate::consts::TERMINAL_GRAVITY;
+use crate::emitter::{Emitter, Origin, Speed, Velocity};
+use crate::figure::Figure;
+use crate::forces::{Force, ForceField};
+use crate::glyphs::GlyphSet;
+use crate::lifetime::Lifetime;
+use crate::palette::Palette;
+use crate::projectile::{Integrator, Projectile};
+use crate::render::RenderOptions;
+use crate::simulation::{self, simulation_widget, Body, ParticleSystem, Simulation};
+use nalgebra::{Point2, Vector2};
+use rand::seq::SliceRandom;
+use ratatui::prelude::*;
+use std::time::Duration;
+
+/// Damping of the spring pulling a particle into place, in multiples of
+/// the inverse gather time. Six leaves a fraction of a cell to snap.
+const STEERING: f32 = 6.0;
+
+#[derive(Debug)]
+pub struct Particle {
+    pub char: char,
+    pub color: Color,
+    pub physics: Projectile,
+    pub life: Lifetime,
+    /// Where in the figure the particle settles.
+    pub target: Point2<f32>,
+    /// Character shown while the figure holds, if not [`char`](Self::char).
+    pub target_char: Option<char>,
+    /// Whether the particle has left the figure.
+    pub scattered: bool,
+}
+
+impl Body for Particle {
+    fn physics(&self) -> &Projectile {
+        &self.physics
+    }
+
+    fn physics_mut(&mut self) -> &mut Projectile {
+        &mut self.physics
+    }
+
+    fn color(&self) -> Color {
+        self.color
+    }
+}
+
+/// Particles fly in from one of the built-in emitters, assemble into a
+/// [`Figure`], hold it, and then scatter and fall under gravity.
+#[derive(Debug)]
+pub struct SimulationStateFormation {
+    /// The particles and what moves and draws them. Its palette is used
+    /// unless the figure has its own colours.
+    pub system: ParticleSystem<Particle>,
+    /// Shape the particles assemble into.
+    pub figure: Figure,
+    /// Simulation whose emitter and glyphs the particles borrow.
+    pub mode: Mode,
+    /// Where and how fast new particles are spawned, or `None` to spawn
+    /// them already in place.
+    pub emitter: Option<Emitter>,
+    /// How long particles take to reach their place in the figure.
+    pub gather: Duration,
+    /// How long the figure holds before it scatters.
+    pub hold: Duration,
+    /// Kick every particle gets when the figure scatters.
+    pub scatter: Velocity,
+}
+
+impl SimulationStateFormation {
+    pub fn new(figure: Figure, mode: Mode) -> Self {
+        Self::with_seed(figure, mode, rand::random())
+    }
+
+    /// Constructs a simulation whose particles are drawn from `seed`, so the
+    /// same input always produces the same frames.
+    pub fn with_seed(figure: Figure, mode: Mode, seed: u64) -> Self {
+        let forces = ForceField::new()
+            .with(Force::LinearDrag(0.8))
+            .with(Force::QuadraticDrag(0.04));
+        Self {
+            system: ParticleSystem::with_seed(seed, forces, GlyphSet::default().for_mode(mode)),
+            figure,
+            mode,
+            emitter: mode.emitter(),
+            gather: Duration::from_secs(2),
+            hold: Duration::from_secs(2),
+            // Bursts out in every direction, biased upward.
+            scatter: Velocity::Cone {
+                direction: -90.0,
+                spread: 180.0,
+                speed: Speed::Uniform([5.0, 25.0]),
+                scale: [1.2, 1.0],
+            },
+        }
+    }
+
+    /// Returns the simulation with particles spawned in place, holding the
+    /// figure for a moment before blowing outward from its centre.
+    pub fn shattering(mut self) -> Self {
+        self.emitter = None;
+        self.gather = Duration::ZERO;
+        self.hold = Duration::from_secs(1);
+        // Inward with a negative scale points away from the centre.
+        self.scatter = Velocity::Inward {
+            speed: Speed::Uniform([10.0, 40.0]),
+            scale: [-1.2, -0.6],
+        };
+        self
+    }
+
+    /// Spawns one particle per point of the figure, centred in `area` and
+    /// shrunk to fit it.
+    fn emit(&mut self, area: Rect) -> usize {
+        let figure = self.figure.fitted(area);
+        let targets = figure.placed(area);
+        let system = &mut self.system;
+        for (point, target) in figure.points.iter().zip(&targets) {
+            let (pos, vel) = match &self.emitter {
+                Some(emitter) => emitter.emit(&mut system.rng, area),
+                None => (*target, Vector2::zeros()),
+            };
+            let char = *system.glyphs.pieces.choose(&mut system.rng).unwrap_or(&'*');
+            let color = match point.color {
+                Some(color) => color,
+                None => system.palette.choose(&mut system.rng),
+            };
+            let physics = system.projectile(pos, vel, Vector2::zeros());
+            system.particles.push(Particle {
+                char,
+                color,
+                physics,
+                life: Lifetime::immortal(system.clock),
+                target: *target,
+                target_char: point.char,
+                scattered: false,
+            });
+        }
+        targets.len()
+    }
+
+    /// Moves every particle by `dt` according to its phase: steering into
+    /// place, holding, or falling once scattered away from the centre of
+    /// `area`.
+    fn step(&mut self, area: Rect, dt: Duration) {
+        let center = Point2::new(f32::from(area.width), f32::from(area.height)) / 2.0;
+        let omega = STEERING / self.gather.as_secs_f32().max(f32::EPSILON);
+        let (clock, integrator) = (self.system.clock, self.system.integrator);
+        let (gather, hold, scatter) = (self.gather, self.hold, &self.scatter);
+        self.system.tick_with(area, dt, |particle, forces, rng| {
+            let age = particle.life.age(clock);
+            if particle.scattered {
+                let gravity = particle.physics.acceleration();
+                particle
+                    .physics
+                    .update_with(dt, |pos, vel| gravity + forces.acceleration(pos, vel));
+            } else if age >= gather + hold {
+                let origin = Origin {
+                    center,
+                    ..Origin::at(particle.target)
+                };
+                let kick = scatter.sample(rng, &origin);
+                particle.physics = Projectile::new(particle.target, kick, TERMINAL_GRAVITY)
+                    .with_integrator(integrator);
+                particle.scattered = true;
+            } else if age >= gather {
+                particle.physics =
+                    Projectile::new(particle.target, Vector2::zeros(), Vector2::zeros())
+                        .with_integrator(integrator);
+            } else {
+                let target = particle.target;
+                // A critically damped spring, so particles settle without
+                // overshooting.
+                particle.physics.update_with(dt, |pos, vel| {
+                    (target - pos) * omega * omega - vel * 2.0 * omega
+                });
+            }
+        });
+    }
+
+    /// Culls every scattered particle that has left `area`.
+    pub fn update(&mut self, area: Rect) {
+        self.system.particles.retain(|particle| {
+            let pos = particle.physics.position();
+            !particle.scattered
+                || (pos.x >= 0.0
+                    && pos.x < area.width as f32
+                    && pos.y >= 0.0
+                    && pos.y < area.height as f32)
+        });
+    }
+
+    /// Returns whether `particle` is holding its place in the figure.
+    fn holding(&self, particle: &Particle) -> bool {
+        !particle.scattered && particle.life.age(self.system.clock) >= self.gather
+    }
+}
+
+impl Simulation for SimulationStateFormation {
+    fn tick(&mut self, area: Rect, dt: Duration) {
+        self.step(area, dt);
+        self.update(area);
+    }
+
+    fn spawn_particles(&mut self, area: Rect) -> usize {
+        self.emit(area)
+    }
+
+    fn set_palette(&mut self, palette: Palette) {
+        self.system.palette = palette;
+    }
+
+    fn set_glyphs(&mut self, glyphs: &GlyphSet) {
+        self.system.set_glyphs(glyphs.for_mode(self.mode));
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        let mut painter = self.system.painter(area, buf);
+        for particle in &self.system.particles {
+            let pos = particle.physics.position();
+            let char = match particle.target_char {
+                Some(char) if self.holding(particle) => char,
+                _ => particle.char,
+            };
+            painter.put(pos.x, pos.y, char, particle.color);
+        }
+    }
+
+    fn resize(&mut self, from: Rect, to: Rect, remap: bool) {
+        if !remap {
+            return;
+        }
+        self.system.remap(from, to);
+        for particle in &mut self.system.particles {
+            particle.target = simulation::remap(particle.target, from, to);
+        }
+    }
+
+    fn particle_count(&self) -> usize {
+        self.system.particles.len()
+    }
+
+    fn reset(&mut self) {
+        self.system.reset();
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.system.reseed(seed);
+    }
+
+    fn set_render_options(&mut self, options: RenderOptions) {
+        self.system.render_options = options;
+    }
+
+    fn set_trail(&mut self, decay: Option<f32>) {
+        self.system.set_trail(decay);
+    }
+
+    fn force_field_mut(&mut self) -> Option<&mut ForceField> {
+        Some(&mut self.system.forces)
+    }
+
+    fn set_integrator(&mut self, integrator: Integrator) {
+        self.system.set_integrator(integrator);
+    }
+}
+
+simulation_widget!(SimulationStateFormation);
@@ -0,0 +1,209 @@
+use crate::color::lerp_oklab;
+use crate::lifetime::ramp;
+use crate::render::Painter;
+use nalgebra::Point2;
+use ratatui::prelude::*;
+use std::time::Duration;
+
+/// Characters of an afterglow cell, from brightest to dimmest.
+pub const TRAIL_RAMP: [char; 3] = ['•', '∙', '·'];
+
+/// Intensity below which an afterglow cell is no longer drawn.
+const MIN_INTENSITY: f32 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TrailCell {
+    intensity: f32,
+    color: Color,
+}
+
+impl Default for TrailCell {
+    fn default() -> Self {
+        Self {
+            intensity: 0.0,
+            color: Color::Reset,
+        }
+    }
+}
+
+/// Afterglow left behind by moving particles.
+///
+/// Every cell a particle passes through lights up at full intensity and then
+/// decays exponentially, so trails follow the real, possibly curved, path of
+/// each particle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrailBuffer {
+    width: u16,
+    height: u16,
+    cells: Vec<TrailCell>,
+    /// Fraction of intensity lost per second, as an exponential rate.
+    decay: f32,
+    /// Characters of a cell, from brightest to dimmest.
+    ramp: Vec<char>,
+}
+
+impl TrailBuffer {
+    /// Constructs an empty [`TrailBuffer`] whose cells lose intensity at
+    /// `decay` per second: after one second a cell keeps `e^-decay` of its
+    /// brightness.
+    pub fn new(decay: f32) -> Self {
+        Self {
+            width: 0,
+            height: 0,
+            cells: vec![],
+            decay: decay.max(0.0),
+            ramp: TRAIL_RAMP.to_vec(),
+        }
+    }
+
+    /// Returns the [`TrailBuffer`] drawing cells with `ramp`, from brightest
+    /// to dimmest, instead of [`TRAIL_RAMP`].
+    pub fn with_ramp(mut self, ramp: Vec<char>) -> Self {
+        self.ramp = ramp;
+        self
+    }
+
+    pub fn decay_rate(&self) -> f32 {
+        self.decay
+    }
+
+    pub fn set_decay_rate(&mut self, decay: f32) {
+        self.decay = decay.max(0.0);
+    }
+
+    /// Matches the buffer to the size of `area`, clearing it if that changed.
+    pub fn resize(&mut self, area: Rect) {
+        if (self.width, self.height) != (area.width, area.height) {
+            self.width = area.width;
+            self.height = area.height;
+            self.cells = vec![TrailCell::default(); area.area() as usize];
+        }
+    }
+
+    /// Removes all afterglow.
+    pub fn clear(&mut self) {
+        self.cells.fill(TrailCell::default());
+    }
+
+    /// Dims every cell by the time elapsed since the last tick.
+    pub fn decay(&mut self, dt: Duration) {
+        let factor = (-self.decay * dt.as_secs_f32()).exp();
+        for cell in &mut self.cells {
+            cell.intensity *= factor;
+        }
+    }
+
+    /// Lights up every cell on the segment a particle travelled along during
+    /// one tick.
+    pub fn deposit(&mut self, from: Point2<f32>, to: Point2<f32>, color: Color) {
+        let delta = to - from;
+        let steps = delta.x.abs().max(delta.y.abs()).ceil();
+        if !steps.is_finite() {
+            return;
+        }
+        let steps = steps.min(f32::from(self.width.max(self.height))) as usize;
+        for step in 0..=steps {
+            let t = if steps == 0 {
+                1.0
+            } else {
+                step as f32 / steps as f32
+            };
+            self.light(from + delta * t, color);
+        }
+    }
+
+    fn light(&mut self, pos: Point2<f32>, color: Color) {
+        if pos.x < 0.0 || pos.y < 0.0 {
+            return;
+        }
+        let (x, y) = (pos.x as usize, pos.y as usize);
+        if x >= self.width as usize || y >= self.height as usize {
+            return;
+        }
+        self.cells[y * self.width as usize + x] = TrailCell {
+            intensity: 1.0,
+            color,
+        };
+    }
+
+    /// Draws the afterglow, fading each cell toward `background`.
+    pub fn render(&self, painter: &mut Painter, background: Color) {
+        if self.width == 0 {
+            return;
+        }
+        for (index, cell) in self.cells.iter().enumerate() {
+            if cell.intensity < MIN_INTENSITY {
+                continue;
+            }
+            let x = (index % self.width as usize) as f32 + 0.5;
+            let y = (index / self.width as usize) as f32 + 0.5;
+            let char = ramp(&self.ramp, 1.0 - cell.intensity).unwrap_or('·');
+            let color = lerp_oklab(cell.color, background, 1.0 - cell.intensity);
+            painter.put(x, y, char, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lit_cells(trail: &TrailBuffer) -> usize {
+        trail
+            .cells
+            .iter()
+            .filter(|cell| cell.intensity >= MIN_INTENSITY)
+            .count()
+    }
+
+    #[test]
+    fn deposit_lights_every_cell_along_the_path() {
+        let mut trail = TrailBuffer::new(1.0);
+        trail.resize(Rect::new(0, 0, 10, 10));
+        trail.deposit(Point2::new(1.5, 1.5), Point2::new(5.5, 1.5), Color::Red);
+        assert_eq!(lit_cells(&trail), 5);
+
+        // Segments leaving the buffer are clipped.
+        trail.deposit(Point2::new(8.5, 8.5), Point2::new(12.5, 12.5), Color::Red);
+        assert_eq!(lit_cells(&trail), 7);
+    }
+
+    #[test]
+    fn afterglow_decays_until_it_disappears() {
+        let mut trail = TrailBuffer::new(3.0);
+        trail.resize(Rect::new(0, 0, 4, 1));
+        trail.deposit(Point2::new(0.0, 0.0), Point2::new(0.0, 0.0), Color::Red);
+
+        trail.decay(Duration::from_millis(100));
+        assert!((trail.cells[0].intensity - (-0.3f32).exp()).abs() < 1e-6);
+
+        trail.decay(Duration::from_secs(1));
+        assert_eq!(lit_cells(&trail), 0);
+    }
+
+    #[test]
+    fn render_dims_older_cells() {
+        let area = Rect::new(0, 0, 2, 1);
+        let mut trail = TrailBuffer::new(2.0);
+        trail.resize(area);
+        trail.deposit(
+            Point2::new(0.0, 0.0),
+            Point2::new(0.0, 0.0),
+            Color::Rgb(255, 0, 0),
+        );
+        trail.decay(Duration::from_millis(500));
+        trail.deposit(
+            Point2::new(1.0, 0.0),
+            Point2::new(1.0, 0.0),
+            Color::Rgb(255, 0, 0),
+        );
+
+        let mut buf = Buffer::empty(area);
+        trail.render(&mut Painter::new(area, &mut buf), Color::Rgb(0, 0, 0));
+
+        assert_eq!(buf.get(0, 0).symbol(), "∙");
+        assert_eq!(buf.get(1, 0).symbol(), "•");
+        assert_eq!(buf.get(1, 0).fg, Color::Rgb(255, 0, 0));
+        assert_ne!(buf.get(0, 0).fg, Color::Rgb(255, 0, 0));
+    }
+}
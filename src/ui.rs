@@ -1,10 +1,11 @@
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Padding, Paragraph};
 use ratatui::Frame;
 
 use crate::app::App;
-use crate::simulation_confetti::SimulationStateConfetti;
-use crate::simulation_fireworks::SimulationStateFireworks;
-use crate::simulation_shooting_star::SimulationStateShootingStar;
-use crate::system::AppSimulation;
+use crate::system::SimulationWidget;
 
 /// Renders the user interface widgets.
 pub fn render(app: &mut App, frame: &mut Frame) {
@@ -14,27 +15,88 @@ pub fn render(app: &mut App, frame: &mut Frame) {
     // - https://github.com/ratatui-org/ratatui/tree/master/examples
     app.set_area(frame.size());
 
-    match app.get_simulation_state() {
-        AppSimulation::Fireworks(_) => {
-            frame.render_stateful_widget(
-                AppSimulation::Fireworks(SimulationStateFireworks::new()),
-                frame.size(),
-                app.get_simulation_state(),
-            );
-        }
-        AppSimulation::Confetti(_) => {
-            frame.render_stateful_widget(
-                AppSimulation::Confetti(SimulationStateConfetti::new()),
-                frame.size(),
-                app.get_simulation_state(),
-            );
-        }
-        AppSimulation::ShootingStar(_) => {
-            frame.render_stateful_widget(
-                AppSimulation::ShootingStar(SimulationStateShootingStar::new()),
-                frame.size(),
-                app.get_simulation_state(),
-            );
-        }
+    for retired in &app.retired {
+        frame.render_widget(retired.as_ref(), frame.size());
     }
+    frame.render_stateful_widget(SimulationWidget, frame.size(), app.get_simulation_state());
+
+    if app.show_help {
+        render_help(app, frame);
+    }
+}
+
+/// Draws the key bindings and the current settings in a box in the middle
+/// of the frame.
+fn render_help(app: &App, frame: &mut Frame) {
+    let bindings: Vec<(String, &str)> = app
+        .keymap
+        .bindings()
+        .filter(|(_, keys)| !keys.is_empty())
+        .map(|(action, keys)| {
+            let keys: Vec<String> = keys.iter().map(ToString::to_string).collect();
+            (keys.join(" "), action.description())
+        })
+        .collect();
+    let keys_width = bindings
+        .iter()
+        .map(|(keys, _)| keys.len())
+        .max()
+        .unwrap_or(0);
+
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let mut lines: Vec<Line> = bindings
+        .into_iter()
+        .map(|(keys, description)| {
+            Line::from(vec![
+                Span::styled(format!("{keys:>keys_width$}"), bold),
+                Span::raw(format!("  {description}")),
+            ])
+        })
+        .collect();
+    lines.push(Line::from(format!(
+        "{:>keys_width$}  spawn a burst",
+        "other keys"
+    )));
+    lines.push(Line::default());
+
+    let mode = app
+        .mode
+        .map_or_else(|| "custom effect".to_string(), |m| m.to_string());
+    let on_off = |flag: bool| if flag { "on" } else { "off" };
+    lines.push(Line::from(format!(
+        "{mode}, speed {}x, intensity {:.2}x",
+        app.speed, app.intensity
+    )));
+    lines.push(Line::from(format!(
+        "paused {}, carry over {}",
+        on_off(app.paused),
+        on_off(app.carry_over)
+    )));
+
+    let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4;
+    let height = lines.len() as u16 + 2;
+    let area = centered(frame.size(), width, height);
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Keys ")
+                .padding(Padding::horizontal(1)),
+        ),
+        area,
+    );
+}
+
+/// Returns a `width` by `height` rectangle in the middle of `area`, shrunk
+/// to fit if necessary.
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
 }
@@ -0,0 +1,116 @@
+use confetty_rs::consts::FIXED_TIMESTEP;
+use confetty_rs::render::{Painter, RenderOptions, Resolution};
+use confetty_rs::simulation::Simulation;
+use confetty_rs::simulation_confetti::SimulationStateConfetti;
+use confetty_rs::simulation_fireworks::SimulationStateFireworks;
+use confetty_rs::simulation_shooting_star::SimulationStateShootingStar;
+use quickcheck::quickcheck;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+
+/// Every simulation, drawn at a resolution picked by `seed`.
+fn simulations(seed: u64) -> [Box<dyn Simulation>; 3] {
+    let resolution =
+        [Resolution::Cell, Resolution::HalfBlock, Resolution::Braille][(seed % 3) as usize];
+    let mut simulations: [Box<dyn Simulation>; 3] = [
+        Box::new(SimulationStateConfetti::with_seed(seed)),
+        Box::new(SimulationStateFireworks::with_seed(seed)),
+        Box::new(SimulationStateShootingStar::with_seed(seed)),
+    ];
+    for simulation in &mut simulations {
+        simulation.set_render_options(RenderOptions {
+            resolution,
+            ..RenderOptions::default()
+        });
+    }
+    simulations
+}
+
+/// Spawns, ticks and renders every simulation into `area` of a buffer
+/// covering `buffer_area`, returning whether every drawn cell stayed inside
+/// `area`.
+fn renders_inside(buffer_area: Rect, area: Rect, ticks: u8, seed: u64) -> bool {
+    simulations(seed).into_iter().all(|mut simulation| {
+        let mut buf = Buffer::empty(buffer_area);
+        for tick in 0..ticks {
+            if tick % 16 == 0 {
+                simulation.spawn_particles(area);
+            }
+            simulation.tick(area, FIXED_TIMESTEP);
+            simulation.render(area, &mut buf);
+        }
+
+        buf.content.iter().enumerate().all(|(i, cell)| {
+            let (x, y) = buf.pos_of(i);
+            cell.symbol() == " " || area.intersects(Rect::new(x, y, 1, 1))
+        })
+    })
+}
+
+quickcheck! {
+    fn no_area_inside_the_buffer_panics(x: u8, y: u8, width: u8, height: u8, ticks: u8, seed: u64) -> bool {
+        let buffer_area = Rect::new(0, 0, 256, 256);
+        let area = Rect::new(x.into(), y.into(), width.into(), height.into())
+            .intersection(buffer_area);
+        renders_inside(buffer_area, area, ticks % 90, seed)
+    }
+
+    fn no_area_overhanging_the_buffer_panics(x: u16, y: u16, width: u8, height: u8, seed: u64) -> bool {
+        let buffer_area = Rect::new(10, 5, 40, 20);
+        let area = Rect::new(x % 80, y % 40, width.into(), height.into());
+        renders_inside(buffer_area, area, 60, seed)
+    }
+
+    fn painter_never_writes_outside_its_area(x: f32, y: f32) -> bool {
+        let area = Rect::new(3, 2, 5, 4);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 12, 10));
+        Painter::new(area, &mut buf).put(x, y, '*', Color::Red);
+        renders_nothing_outside(&buf, area)
+    }
+}
+
+fn renders_nothing_outside(buf: &Buffer, area: Rect) -> bool {
+    buf.content.iter().enumerate().all(|(i, cell)| {
+        let (x, y) = buf.pos_of(i);
+        cell.symbol() == " " || area.intersects(Rect::new(x, y, 1, 1))
+    })
+}
+
+#[test]
+fn zero_sized_areas_do_not_panic() {
+    for (width, height) in [(0, 0), (0, 10), (10, 0), (1, 1)] {
+        for seed in 0..3 {
+            assert!(renders_inside(
+                Rect::new(0, 0, 20, 20),
+                Rect::new(4, 4, width, height),
+                120,
+                seed
+            ));
+        }
+    }
+}
+
+#[test]
+fn painter_offsets_by_area_origin() {
+    let area = Rect::new(3, 2, 5, 4);
+    let mut buf = Buffer::empty(Rect::new(0, 0, 12, 10));
+    let mut painter = Painter::new(area, &mut buf);
+    painter.put(0.0, 0.0, 'a', Color::Red);
+    painter.put(4.9, 3.9, 'b', Color::Red);
+    painter.put(-0.1, 0.0, 'x', Color::Red);
+    painter.put(5.0, 0.0, 'x', Color::Red);
+    painter.put(0.0, f32::NAN, 'x', Color::Red);
+    drop(painter);
+
+    assert_eq!(buf.get(3, 2).symbol(), "a");
+    assert_eq!(buf.get(7, 5).symbol(), "b");
+    assert!(renders_nothing_outside(&buf, area));
+    assert_eq!(
+        buf.content
+            .iter()
+            .filter(|cell| cell.symbol() != " ")
+            .count(),
+        2
+    );
+}
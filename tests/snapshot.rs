@@ -0,0 +1,776 @@
+use confetty_rs::app::App;
+use confetty_rs::app::Mode;
+use confetty_rs::color::to_ansi256;
+use confetty_rs::consts::FIXED_TIMESTEP;
+use confetty_rs::effect::{EffectDefinition, SimulationStateEffect};
+use confetty_rs::font::Font;
+use confetty_rs::forces::Force;
+use confetty_rs::glyphs::GlyphSet;
+use confetty_rs::handler::{handle_key_events, handle_mouse_events};
+use confetty_rs::image;
+use confetty_rs::palette::{ColorSupport, Palette};
+use confetty_rs::render::{RenderOptions, Resolution};
+use confetty_rs::schedule::Schedule;
+use confetty_rs::simulation::Simulation;
+use confetty_rs::simulation_confetti::SimulationStateConfetti;
+use confetty_rs::simulation_fireworks::SimulationStateFireworks;
+use confetty_rs::simulation_formation::SimulationStateFormation;
+use confetty_rs::simulation_shooting_star::SimulationStateShootingStar;
+use confetty_rs::ui;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use nalgebra::{Point2, Vector2};
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use ratatui::widgets::{Block, Borders};
+use ratatui::Terminal;
+use std::fs;
+use std::path::PathBuf;
+
+const SEED: u64 = 0x5EED;
+
+/// Drives an [`App`] the way the main loop does, but against a
+/// [`TestBackend`] and with a fixed time step.
+struct Harness {
+    app: App,
+    terminal: Terminal<TestBackend>,
+}
+
+impl Harness {
+    fn new(simulation: Box<dyn Simulation>, width: u16, height: u16) -> Self {
+        Self::with_app(App::with_simulation(simulation), width, height)
+    }
+
+    fn with_app(app: App, width: u16, height: u16) -> Self {
+        let terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        let mut harness = Self { app, terminal };
+        harness.draw();
+        harness
+    }
+
+    fn confetti(width: u16, height: u16) -> Self {
+        let mut harness = Self::new(
+            Box::new(SimulationStateConfetti::with_seed(SEED)),
+            width,
+            height,
+        );
+        harness.app.mode = Some(Mode::Confetti);
+        harness
+    }
+
+    fn fireworks(width: u16, height: u16) -> Self {
+        let mut harness = Self::new(
+            Box::new(SimulationStateFireworks::with_seed(SEED)),
+            width,
+            height,
+        );
+        harness.app.mode = Some(Mode::Fireworks);
+        harness
+    }
+
+    fn shooting_star(width: u16, height: u16) -> Self {
+        let mut harness = Self::new(
+            Box::new(SimulationStateShootingStar::with_seed(SEED)),
+            width,
+            height,
+        );
+        harness.app.mode = Some(Mode::Stars);
+        harness
+    }
+
+    fn effect(name: &str, width: u16, height: u16) -> Self {
+        let definition = EffectDefinition::builtin(name).unwrap();
+        Self::new(
+            Box::new(SimulationStateEffect::with_seed(definition, SEED).unwrap()),
+            width,
+            height,
+        )
+    }
+
+    fn text(text: &str, mode: Mode, width: u16, height: u16) -> Self {
+        let figure = Font::default().render(text);
+        Self::new(
+            Box::new(SimulationStateFormation::with_seed(figure, mode, SEED)),
+            width,
+            height,
+        )
+    }
+
+    fn draw(&mut self) {
+        self.terminal
+            .draw(|frame| ui::render(&mut self.app, frame))
+            .unwrap();
+    }
+
+    fn spawn(&mut self) {
+        self.app.spawn_particles();
+        self.draw();
+    }
+
+    /// Advances the simulation by `ticks` fixed steps, drawing after each.
+    fn run(&mut self, ticks: usize) {
+        for _ in 0..ticks {
+            self.app.tick(FIXED_TIMESTEP);
+            self.draw();
+        }
+    }
+
+    /// Runs until `done` holds, returning the number of ticks it took.
+    fn run_until(&mut self, max_ticks: usize, done: impl Fn(&Self) -> bool) -> Option<usize> {
+        (1..=max_ticks).find(|_| {
+            self.run(1);
+            done(self)
+        })
+    }
+
+    fn key(&mut self, c: char) {
+        let event = KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        handle_key_events(event, &mut self.app).unwrap();
+        self.draw();
+    }
+
+    fn mouse(&mut self, kind: MouseEventKind, column: u16, row: u16) {
+        let event = MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+        handle_mouse_events(event, &mut self.app).unwrap();
+        self.draw();
+    }
+
+    /// Resizes the terminal the way a resize event does in the main loop.
+    fn resize(&mut self, width: u16, height: u16) {
+        self.terminal.backend_mut().resize(width, height);
+        self.app.set_area(Rect::new(0, 0, width, height));
+        self.draw();
+    }
+
+    fn particle_count(&self) -> usize {
+        self.app.state.particle_count()
+    }
+
+    fn buffer(&self) -> &Buffer {
+        self.terminal.backend().buffer()
+    }
+
+    fn lines(&self) -> Vec<String> {
+        let buffer = self.buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer.get(x, y).symbol())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn filled_cells(&self) -> usize {
+        self.buffer()
+            .content
+            .iter()
+            .filter(|cell| cell.symbol() != " ")
+            .count()
+    }
+
+    /// Compares the frame with `tests/snapshots/<name>.snap`.
+    ///
+    /// Run with `UPDATE_SNAPSHOTS=1` to accept the current output.
+    fn assert_snapshot(&self, name: &str) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/snapshots")
+            .join(format!("{name}.snap"));
+        let actual = self.lines().join("\n") + "\n";
+
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::write(&path, &actual).unwrap();
+            return;
+        }
+
+        let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+            panic!("missing snapshot {path:?}, run with UPDATE_SNAPSHOTS=1 to create it")
+        });
+        assert_eq!(actual, expected, "frame differs from snapshot {path:?}");
+    }
+}
+
+#[test]
+fn same_seed_renders_identical_frames() {
+    let constructors: [fn(u16, u16) -> Harness; 3] = [
+        Harness::confetti,
+        Harness::fireworks,
+        Harness::shooting_star,
+    ];
+
+    for constructor in constructors {
+        let mut a = constructor(60, 30);
+        let mut b = constructor(60, 30);
+        for _ in 0..3 {
+            a.spawn();
+            b.spawn();
+            a.run(10);
+            b.run(10);
+        }
+        assert_eq!(a.buffer(), b.buffer());
+        assert_eq!(a.particle_count(), b.particle_count());
+    }
+}
+
+#[test]
+fn different_seeds_render_different_frames() {
+    let mut a = Harness::confetti(60, 30);
+    let mut b = Harness::new(
+        Box::new(SimulationStateConfetti::with_seed(SEED + 1)),
+        60,
+        30,
+    );
+    a.spawn();
+    b.spawn();
+    a.run(5);
+    b.run(5);
+    assert_ne!(a.buffer(), b.buffer());
+}
+
+#[test]
+fn tick_jitter_does_not_change_the_outcome() {
+    let mut steady = Harness::confetti(60, 30);
+    let mut jittery = Harness::confetti(60, 30);
+    steady.spawn();
+    jittery.spawn();
+
+    steady.run(12);
+    // The same 12 steps, delivered as uneven ticks.
+    for ticks in [1, 3, 2, 4, 2] {
+        jittery.app.tick(FIXED_TIMESTEP * ticks);
+    }
+    jittery.draw();
+
+    assert_eq!(steady.buffer(), jittery.buffer());
+}
+
+#[test]
+fn confetti_spawns_a_burst_and_falls_out_of_view() {
+    let mut harness = Harness::confetti(60, 30);
+    harness.spawn();
+    assert_eq!(harness.particle_count(), 75);
+    assert!(harness.filled_cells() > 0);
+
+    let ticks = harness.run_until(2_000, |h| h.particle_count() == 0);
+    assert!(ticks.is_some(), "confetti never left the screen");
+    assert_eq!(harness.filled_cells(), 0);
+}
+
+#[test]
+fn fireworks_rocket_explodes_at_its_apex() {
+    let mut harness = Harness::fireworks(60, 30);
+    harness.spawn();
+    assert_eq!(harness.particle_count(), 1);
+    assert!(harness.lines().iter().any(|line| line.contains('▄')));
+
+    harness
+        .run_until(200, |h| h.particle_count() > 1)
+        .expect("rocket never exploded");
+    assert_eq!(harness.particle_count(), 50);
+    assert!(!harness.lines().iter().any(|line| line.contains('▄')));
+}
+
+#[test]
+fn shooting_star_bursts_when_it_hits_a_wall() {
+    let mut harness = Harness::shooting_star(60, 30);
+    harness.spawn();
+    assert_eq!(harness.particle_count(), 1);
+    assert!(harness.lines().iter().any(|line| line.contains('●')));
+
+    harness
+        .run_until(200, |h| h.particle_count() > 1)
+        .expect("shooting star never reached a wall");
+    assert_eq!(harness.particle_count(), 40);
+    assert!(!harness.lines().iter().any(|line| line.contains('●')));
+}
+
+#[test]
+fn wind_carries_shooting_stars() {
+    let area = Rect::new(0, 0, 120, 60);
+    let drift = |wind: Option<Vector2<f32>>| {
+        let mut stars = SimulationStateShootingStar::with_seed(SEED);
+        if let Some(wind) = wind {
+            stars.system.forces.push(Force::Wind(wind));
+        }
+        stars.spawn_particles(area);
+        for _ in 0..5 {
+            stars.tick(area, FIXED_TIMESTEP);
+        }
+        assert_eq!(stars.system.particles.len(), 1, "star hit a wall");
+        stars.system.particles[0].physics.position().x
+    };
+    assert!(drift(Some(Vector2::new(40.0, 0.0))) > drift(None));
+}
+
+#[test]
+fn crackle_effect_sparks_crackle_as_they_burn_out() {
+    let mut harness = Harness::effect("crackle", 60, 30);
+    harness.spawn();
+    assert_eq!(harness.particle_count(), 1);
+
+    harness
+        .run_until(200, |h| h.particle_count() > 1)
+        .expect("rocket never exploded");
+    assert_eq!(harness.particle_count(), 24);
+    harness
+        .run_until(200, |h| h.lines().iter().any(|line| line.contains('✦')))
+        .expect("sparks never crackled");
+}
+
+#[test]
+fn user_defined_effect_runs_in_the_app() {
+    let source = r##"
+        name = "fountain"
+        [[emitters]]
+        particle = "drop"
+        rate = { burst = 12 }
+        shape = { type = "point", at = [0.5, 1.0] }
+        velocity = { type = "cone", direction = -90.0, spread = 20.0, speed = [15.0, 20.0] }
+        [particles.drop]
+        chars = "o"
+        palette = ["#3fa9f5"]
+    "##;
+    let definition = EffectDefinition::from_toml(source).unwrap();
+    let mut harness = Harness::new(
+        Box::new(SimulationStateEffect::with_seed(definition, SEED).unwrap()),
+        60,
+        30,
+    );
+    harness.spawn();
+    assert_eq!(harness.particle_count(), 12);
+    harness.run(10);
+    assert!(harness.lines().iter().any(|line| line.contains('o')));
+
+    harness
+        .run_until(2_000, |h| h.particle_count() == 0)
+        .expect("drops never fell back out of view");
+}
+
+#[test]
+fn schedule_spawns_without_input() {
+    let mut harness = Harness::fireworks(60, 30);
+    harness.app.schedule = Some(Schedule::with_seed(2.0, 0.0, SEED));
+
+    harness.run(1);
+    assert_eq!(harness.app.num_particles, 1);
+    // Two more rockets over the next second, at 0.5 s intervals.
+    harness.run(31);
+    assert_eq!(harness.app.num_particles, 3);
+}
+
+#[test]
+fn click_spawns_confetti_at_the_cursor() {
+    let mut harness = Harness::confetti(60, 30);
+    harness.mouse(MouseEventKind::Down(MouseButton::Left), 45, 20);
+    assert_eq!(harness.particle_count(), 75);
+    assert_ne!(harness.buffer().get(45, 20).symbol(), " ");
+    assert_eq!(harness.lines()[0].trim(), "", "spawned at the top as usual");
+}
+
+#[test]
+fn click_launches_a_firework_that_bursts_at_the_cursor() {
+    let area = Rect::new(0, 0, 60, 30);
+    let target = Point2::new(20.5, 8.5);
+    let mut fireworks = SimulationStateFireworks::with_seed(SEED);
+    assert_eq!(fireworks.spawn_at(area, target), 1);
+
+    (0..200)
+        .find(|_| {
+            fireworks.tick(area, FIXED_TIMESTEP);
+            fireworks.particle_count() > 1
+        })
+        .expect("rocket never exploded");
+    let burst = fireworks.system.particles[0].physics.position();
+    assert!((burst.x - target.x).abs() < 1.0, "burst at {burst}");
+    assert!((burst.y - target.y).abs() < 2.0, "burst at {burst}");
+}
+
+#[test]
+fn click_aims_shooting_stars_at_the_cursor() {
+    let area = Rect::new(0, 0, 60, 30);
+    let target = Point2::new(50.5, 5.5);
+    let mut stars = SimulationStateShootingStar::with_seed(SEED);
+    stars.spawn_at(area, target);
+
+    let star = &stars.system.particles[0].physics;
+    let aim = (target - star.position()).normalize();
+    assert!(star.velocity().normalize().dot(&aim) > 0.999);
+}
+
+#[test]
+fn dragging_streams_particles_until_release() {
+    let mut harness = Harness::fireworks(60, 30);
+    harness.mouse(MouseEventKind::Down(MouseButton::Left), 10, 10);
+    harness.mouse(MouseEventKind::Drag(MouseButton::Left), 30, 10);
+    harness.run(30);
+    let streamed = harness.app.num_particles;
+    assert!(streamed > 1, "only {streamed} rockets while dragging");
+
+    harness.mouse(MouseEventKind::Up(MouseButton::Left), 30, 10);
+    harness.run(30);
+    assert_eq!(harness.app.num_particles, streamed);
+}
+
+#[test]
+fn scrolling_adjusts_intensity() {
+    let mut harness = Harness::confetti(60, 30);
+    harness.mouse(MouseEventKind::ScrollUp, 0, 0);
+    harness.mouse(MouseEventKind::ScrollUp, 0, 0);
+    harness.spawn();
+    assert_eq!(harness.particle_count(), 117);
+
+    for _ in 0..20 {
+        harness.mouse(MouseEventKind::ScrollDown, 0, 0);
+    }
+    harness.app.state.reset();
+    harness.spawn();
+    assert_eq!(harness.particle_count(), 8);
+}
+
+#[test]
+fn resize_remaps_particles_proportionally() {
+    let from = Rect::new(0, 0, 40, 20);
+    let to = Rect::new(0, 0, 80, 60);
+    let mut confetti = SimulationStateConfetti::with_seed(SEED);
+    confetti.spawn_particles(from);
+    confetti.tick(from, FIXED_TIMESTEP * 10);
+    let before: Vec<_> = confetti
+        .system
+        .particles
+        .iter()
+        .map(|particle| particle.physics.position())
+        .collect();
+
+    confetti.resize(from, to, true);
+    for (particle, pos) in confetti.system.particles.iter().zip(before) {
+        let expected = Point2::new(pos.x * 2.0, pos.y * 3.0);
+        assert!((particle.physics.position() - expected).norm() < 1e-3);
+    }
+}
+
+#[test]
+fn shrinking_culls_particles_unless_remapped() {
+    let settled = |remap_on_resize: bool| {
+        let mut harness = Harness::confetti(60, 30);
+        harness.app.remap_on_resize = remap_on_resize;
+        harness.spawn();
+        harness.run(20);
+        let before = harness.particle_count();
+        harness.resize(20, 10);
+        harness.run(1);
+        (before, harness.particle_count())
+    };
+
+    let (before, after) = settled(false);
+    assert!(after < before, "{after} of {before} particles left");
+    let (before, after) = settled(true);
+    assert_eq!(after, before);
+}
+
+#[test]
+fn switching_modes_clears_particles_unless_carried_over() {
+    let mut harness = Harness::confetti(60, 30);
+    harness.spawn();
+    harness.key('2');
+    assert_eq!(harness.app.mode, Some(Mode::Fireworks));
+    assert_eq!(harness.particle_count(), 0);
+    assert_eq!(harness.filled_cells(), 0);
+
+    harness.key('o');
+    harness.key('1');
+    harness.spawn();
+    harness.key('3');
+    assert_eq!(harness.app.mode, Some(Mode::Stars));
+    assert_eq!(harness.app.retired.len(), 1);
+    assert!(harness.filled_cells() > 0);
+
+    let finished = harness.run_until(600, |harness| harness.app.retired.is_empty());
+    assert!(finished.is_some(), "carried over confetti never finished");
+}
+
+#[test]
+fn pause_freezes_time_until_stepped() {
+    let mut harness = Harness::confetti(60, 30);
+    harness.spawn();
+    harness.key(' ');
+    let frozen = harness.lines();
+    harness.run(10);
+    assert_eq!(harness.lines(), frozen);
+
+    harness.key('.');
+    assert_ne!(harness.lines(), frozen);
+
+    let mut running = Harness::confetti(60, 30);
+    running.spawn();
+    running.run(1);
+    assert_eq!(harness.lines(), running.lines());
+}
+
+#[test]
+fn speed_scales_simulated_time() {
+    let mut fast = Harness::confetti(60, 30);
+    fast.key(']');
+    fast.spawn();
+    fast.run(10);
+
+    let mut normal = Harness::confetti(60, 30);
+    normal.spawn();
+    normal.run(20);
+    assert_eq!(fast.lines(), normal.lines());
+}
+
+#[test]
+fn switched_modes_use_the_palette_downgraded_for_the_terminal() {
+    let gold = Palette::builtin("gold").unwrap();
+    let mut harness = Harness::confetti(60, 30);
+    harness.app.settings.palette = Some(gold.clone());
+    harness.app.settings.render_options.colors = ColorSupport::Ansi256;
+    harness.key('2');
+    harness.spawn();
+    harness.run(5);
+
+    let allowed: Vec<_> = gold.colors().iter().copied().map(to_ansi256).collect();
+    let drawn: Vec<_> = harness
+        .buffer()
+        .content
+        .iter()
+        .filter(|cell| cell.symbol() != " ")
+        .map(|cell| cell.fg)
+        .collect();
+    assert!(!drawn.is_empty());
+    assert!(drawn.iter().all(|color| allowed.contains(color)));
+}
+
+#[test]
+fn ascii_mode_draws_only_seven_bit_characters() {
+    let mut harness = Harness::shooting_star(60, 30);
+    harness.app.settings.seed = Some(SEED);
+    harness.app.settings.glyphs = Some(GlyphSet::Ascii);
+    harness.app.settings.render_options = RenderOptions {
+        resolution: Resolution::Braille,
+        ascii: true,
+        ..RenderOptions::default()
+    };
+    harness.app.settings.trail = Some(3.0);
+    harness.key('3');
+    harness.spawn();
+    harness.run(40);
+
+    assert!(harness.filled_cells() > 0);
+    for line in harness.lines() {
+        assert!(
+            line.chars().all(|c| c.is_ascii_graphic() || c == ' '),
+            "{line}"
+        );
+    }
+}
+
+#[test]
+fn emoji_keep_their_second_cell_blank() {
+    let mut harness = Harness::confetti(60, 30);
+    harness.app.settings.seed = Some(SEED);
+    harness.app.settings.glyphs = Some(GlyphSet::Emoji);
+    harness.key('1');
+    harness.spawn();
+    harness.run(15);
+
+    // The backend skips the cell after a double-width character, so check
+    // the frame itself.
+    let mut buffer = Buffer::empty(harness.app.area);
+    harness.app.state.render(harness.app.area, &mut buffer);
+    let mut emoji = 0;
+    for y in 0..buffer.area.height {
+        for x in 1..buffer.area.width {
+            if buffer
+                .get(x - 1, y)
+                .symbol()
+                .chars()
+                .any(|c| c > '\u{ffff}')
+            {
+                emoji += 1;
+                assert_eq!(buffer.get(x, y).symbol(), " ", "cell ({x}, {y})");
+            }
+        }
+    }
+    assert!(emoji > 0);
+}
+
+#[test]
+fn text_assembles_holds_and_scatters() {
+    let mut harness = Harness::text("Hi!", Mode::Fireworks, 40, 15);
+    harness.spawn();
+    let points = harness.particle_count();
+    assert_eq!(points, Font::default().render("HI!").points.len());
+
+    // Two seconds to gather, then the figure holds for two more.
+    let second = (1000 / FIXED_TIMESTEP.as_millis()) as usize;
+    harness.run(2 * second);
+    let formed = harness.lines();
+    assert_eq!(harness.filled_cells(), points);
+    harness.run(second);
+    assert_eq!(harness.lines(), formed, "the figure did not hold");
+
+    harness.run(2 * second);
+    assert_ne!(harness.lines(), formed, "the figure never scattered");
+    let ticks = harness.run_until(2_000, |h| h.particle_count() == 0);
+    assert!(ticks.is_some(), "scattered particles never left the screen");
+}
+
+#[test]
+fn text_shows_without_input() {
+    let figure = Font::default().render("Hi");
+    let mut app = App::with_simulation(Box::new(SimulationStateFormation::with_seed(
+        figure.clone(),
+        Mode::Confetti,
+        SEED,
+    )));
+    app.spawn_on_start = true;
+    let mut harness = Harness::with_app(app, 40, 15);
+    assert_eq!(harness.particle_count(), figure.points.len());
+
+    // Only the first frame spawns.
+    harness.run(5);
+    assert_eq!(harness.particle_count(), figure.points.len());
+}
+
+#[test]
+fn images_shatter_outward_keeping_their_colour() {
+    let mut figure = image::from_ascii_art("#####\n#####\n#####\n");
+    for point in &mut figure.points {
+        point.color = Some(Color::Rgb(10, 200, 30));
+    }
+    let simulation = SimulationStateFormation::with_seed(figure, Mode::Confetti, SEED);
+    let mut harness = Harness::new(Box::new(simulation.shattering()), 40, 15);
+    harness.spawn();
+    assert_eq!(harness.particle_count(), 15);
+    assert_eq!(harness.lines()[7], format!("{:17}#####{:18}", "", ""));
+
+    // Pieces fly apart after holding for a second, and keep their colour.
+    harness.run(45);
+    assert!(harness.filled_cells() > 0);
+    assert_ne!(harness.lines()[7], format!("{:17}#####{:18}", "", ""));
+    let colors: Vec<_> = harness
+        .buffer()
+        .content
+        .iter()
+        .filter(|cell| cell.symbol() != " ")
+        .map(|cell| cell.fg)
+        .collect();
+    assert!(colors.iter().all(|&color| color == Color::Rgb(10, 200, 30)));
+
+    let ticks = harness.run_until(2_000, |h| h.particle_count() == 0);
+    assert!(ticks.is_some(), "pieces never left the screen");
+}
+
+#[test]
+fn help_overlay_snapshot() {
+    let mut harness = Harness::confetti(60, 30);
+    harness.spawn();
+    harness.run(10);
+    harness.key('?');
+    harness.assert_snapshot("help_overlay");
+
+    harness.key('?');
+    assert!(!harness.app.show_help);
+}
+
+#[test]
+fn confetti_snapshot() {
+    let mut harness = Harness::confetti(40, 16);
+    harness.spawn();
+    harness.run(8);
+    harness.assert_snapshot("confetti");
+}
+
+#[test]
+fn fireworks_snapshot() {
+    let mut harness = Harness::fireworks(40, 36);
+    harness.spawn();
+    harness.run(10);
+    harness.spawn();
+    harness.run(80);
+    harness.assert_snapshot("fireworks");
+}
+
+#[test]
+fn fireworks_braille_snapshot() {
+    let mut harness = Harness::fireworks(40, 36);
+    harness.app.state.set_render_options(RenderOptions {
+        resolution: Resolution::Braille,
+        ..RenderOptions::default()
+    });
+    harness.spawn();
+    harness.run(10);
+    harness.spawn();
+    harness.run(80);
+    harness.assert_snapshot("fireworks_braille");
+}
+
+#[test]
+fn shooting_star_trail_snapshot() {
+    let mut harness = Harness::shooting_star(40, 16);
+    harness.app.state.set_trail(Some(2.0));
+    harness.spawn();
+    harness.run(20);
+    harness.assert_snapshot("shooting_star_trail");
+}
+
+#[test]
+fn shooting_star_snapshot() {
+    let mut harness = Harness::shooting_star(40, 16);
+    harness.spawn();
+    harness.run(6);
+    harness.assert_snapshot("shooting_star");
+}
+
+#[test]
+fn text_snapshot() {
+    let mut harness = Harness::text("Shipped v2!", Mode::Confetti, 80, 12);
+    harness.spawn();
+    harness.run(90);
+    harness.assert_snapshot("text");
+}
+
+#[test]
+fn simulation_renders_inside_a_block() {
+    let mut confetti = SimulationStateConfetti::with_seed(SEED);
+    let mut terminal = Terminal::new(TestBackend::new(30, 12)).unwrap();
+    let outer = Rect::new(10, 2, 20, 10);
+    let block = Block::default().borders(Borders::ALL);
+    let inner = block.inner(outer);
+
+    confetti.spawn_particles(inner);
+    for _ in 0..5 {
+        confetti.tick(inner, FIXED_TIMESTEP);
+    }
+    terminal
+        .draw(|frame| {
+            frame.render_widget(block, outer);
+            frame.render_widget(&confetti, inner);
+        })
+        .unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let mut drawn = 0;
+    for (i, cell) in buffer.content.iter().enumerate() {
+        let (x, y) = buffer.pos_of(i);
+        let on_border = x == outer.left()
+            || x == outer.right() - 1
+            || y == outer.top()
+            || y == outer.bottom() - 1;
+        if outer.intersects(Rect::new(x, y, 1, 1)) && on_border {
+            assert!("┌┐└┘─│".contains(cell.symbol()), "border overwritten");
+        } else if !inner.intersects(Rect::new(x, y, 1, 1)) {
+            assert_eq!(cell.symbol(), " ", "drew outside the block at ({x}, {y})");
+        } else if cell.symbol() != " " {
+            drawn += 1;
+        }
+    }
+    assert!(drawn > 0);
+}